@@ -1,16 +1,24 @@
-use std::fmt::Debug;
-use view::Renderable;
-use opengl_graphics::GlGraphics;
-use piston_window::*;
-
-#[derive(Debug)]
+use error::ChessError;
+#[cfg(feature = "serialization")]
+use serialization::SavedBoard;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+use std::fmt::{self, Debug};
+use theme::Theme;
+use view::{Renderable, DrawBackend, BoardLayout, SQUARE_SIZE, draw_board_squares, draw_board_coordinates};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialization", serde(into = "SavedBoard<T>", try_from = "SavedBoard<T>"))]
 #[allow(dead_code)]
 pub struct ChessBoard<T: Clone + Debug> {
-	size: u8,
+	width: u8, // Number of columns
+	height: u8, // Number of rows
 	columns: Vec<Vec<ChessBoardCell<T>>>
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 #[allow(dead_code)]
 pub enum ChessPieceType {
 	Pawn,
@@ -18,16 +26,21 @@ pub enum ChessPieceType {
 	Knight,
 	Bishop,
 	Queen,
-	King
+	King,
+	Fairy(char) // A piece defined by Betza notation, named by its uppercase letter, see `fairy`
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Direction {
-	Up,
-	Down
-}
+pub const PIECE_TYPES: [ChessPieceType; 6] = [
+	ChessPieceType::Pawn,
+	ChessPieceType::Rook,
+	ChessPieceType::Knight,
+	ChessPieceType::Bishop,
+	ChessPieceType::Queen,
+	ChessPieceType::King
+];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 #[allow(dead_code)]
 pub enum ChessPieceColor {
 	Black,
@@ -44,13 +57,29 @@ impl ChessPieceColor {
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 #[allow(dead_code)]
 pub struct ChessPiece {
 	type_name: ChessPieceType,
 	color: ChessPieceColor
 }
 
+impl ChessPieceType {
+	// Conventional material value in pawns, the king is not counted. Fairy pieces are valued by
+	// their definition, see `ChessGame::get_piece_value`.
+	pub fn get_value(&self) -> u32 {
+		match self {
+			&ChessPieceType::Pawn => 1,
+			&ChessPieceType::Knight | &ChessPieceType::Bishop => 3,
+			&ChessPieceType::Rook => 5,
+			&ChessPieceType::Queen => 9,
+			&ChessPieceType::King => 0,
+			&ChessPieceType::Fairy(_) => 0
+		}
+	}
+}
+
 impl ChessPiece {
 	pub fn new(type_name: ChessPieceType, color: ChessPieceColor) -> ChessPiece {
 		ChessPiece {
@@ -66,9 +95,47 @@ impl ChessPiece {
 	pub fn get_color(&self) -> ChessPieceColor {
 		self.color
 	}
+
+	// Unicode chess figurine, e.g. '♘' for a white knight. Fairy pieces have none and use their letter.
+	pub fn to_figurine(&self) -> char {
+		match (self.color, self.type_name) {
+			(_, ChessPieceType::Fairy(_)) => self.to_letter(),
+			(ChessPieceColor::White, ChessPieceType::King) => '\u{2654}',
+			(ChessPieceColor::White, ChessPieceType::Queen) => '\u{2655}',
+			(ChessPieceColor::White, ChessPieceType::Rook) => '\u{2656}',
+			(ChessPieceColor::White, ChessPieceType::Bishop) => '\u{2657}',
+			(ChessPieceColor::White, ChessPieceType::Knight) => '\u{2658}',
+			(ChessPieceColor::White, ChessPieceType::Pawn) => '\u{2659}',
+			(ChessPieceColor::Black, ChessPieceType::King) => '\u{265A}',
+			(ChessPieceColor::Black, ChessPieceType::Queen) => '\u{265B}',
+			(ChessPieceColor::Black, ChessPieceType::Rook) => '\u{265C}',
+			(ChessPieceColor::Black, ChessPieceType::Bishop) => '\u{265D}',
+			(ChessPieceColor::Black, ChessPieceType::Knight) => '\u{265E}',
+			(ChessPieceColor::Black, ChessPieceType::Pawn) => '\u{265F}'
+		}
+	}
+
+	// Letter as used in FEN, uppercase for white and lowercase for black
+	pub fn to_letter(&self) -> char {
+		let (white, black) = match self.type_name {
+			ChessPieceType::Pawn => ('P', 'p'),
+			ChessPieceType::Rook => ('R', 'r'),
+			ChessPieceType::Knight => ('N', 'n'),
+			ChessPieceType::Bishop => ('B', 'b'),
+			ChessPieceType::Queen => ('Q', 'q'),
+			ChessPieceType::King => ('K', 'k'),
+			ChessPieceType::Fairy(letter) => (letter, letter.to_ascii_lowercase())
+		};
+
+		match self.color {
+			ChessPieceColor::White => white,
+			ChessPieceColor::Black => black
+		}
+	}
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 #[allow(dead_code)]
 pub struct BoardCoordinates {
 	pub row: u8,
@@ -90,54 +157,118 @@ impl Into<BoardCoordinates> for (u8, u8) {
 #[allow(dead_code)]
 impl ToString for BoardCoordinates {
 	fn to_string(&self) -> String {
-		match String::from_utf8(vec![self.col + 'a' as u8, self.row + '1' as u8]) {
-			Ok(v) => v,
-			Err(_) => panic!("Coordinates were not valid utf8: {}, {}", self.col, self.row)
+		format!("{}{}", self.get_file_name(), self.get_rank_name())
+	}
+}
+
+// Coordinates checked to be on a board of a given width and height. Squares can only be made by a board
+// and remember its size, so a board refuses a square made for one of another size instead of reading
+// past its edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Square {
+	col: u8,
+	row: u8,
+	width: u8, // Size of the board the square was made for
+	height: u8
+}
+
+impl Square {
+	pub fn get_col(&self) -> u8 {
+		self.col
+	}
+
+	pub fn get_row(&self) -> u8 {
+		self.row
+	}
+
+	pub fn get_coordinates(&self) -> BoardCoordinates {
+		BoardCoordinates {
+			col: self.col,
+			row: self.row
 		}
 	}
 }
 
+impl ToString for Square {
+	fn to_string(&self) -> String {
+		self.get_coordinates().to_string()
+	}
+}
+
 #[allow(dead_code)]
 impl BoardCoordinates {
+	// Letters naming the column: 'a' to 'z', then 'aa', 'ab' and so on past the 26th column
+	pub fn get_file_name(&self) -> String {
+		let mut letters = Vec::new();
+		let mut remaining = self.col as u32 + 1;
+		while remaining > 0 {
+			remaining -= 1;
+			letters.push((b'a' + (remaining % 26) as u8) as char);
+			remaining /= 26;
+		}
+		letters.iter().rev().cloned().collect()
+	}
+
+	// The row counted from 1, which may run past 9 on tall boards
+	pub fn get_rank_name(&self) -> String {
+		(self.row as u32 + 1).to_string()
+	}
+
 	// Convert a character to the correct column # ('a'=0, 'b'=1, ...)
-	fn char_to_column(col: char) -> u8 {
+	pub fn char_to_column(col: char) -> Option<u8> {
 		match col {
-			input @ 'a' ... 'h' => input as u8 - ('a' as u8),
-			_  => panic!("Invalid column name: {}.", col)
+			input @ 'a' ... 'z' => Some(input as u8 - ('a' as u8)),
+			_  => None
 		}
 	}
 
-	// Convert a character to the correct row # ('1'=0, '2'=1, ...)
-	fn char_to_row(row: char) -> u8 {
-		match row {
-			input @ '1' ... '8' => input as u8 - ('1' as u8),
-			_  => panic!("Invalid row number: {}.", row)
+	// Convert a file name as written by `get_file_name` to its column
+	pub fn file_to_column(file: &str) -> Option<u8> {
+		if file.is_empty() {
+			return None;
+		}
+
+		let mut number = 0u32;
+		for letter in file.chars() {
+			match BoardCoordinates::char_to_column(letter) {
+				Some(value) => { number = number * 26 + value as u32 + 1; },
+				None => { return None; }
+			}
+			if number > 256 {
+				return None;
+			}
 		}
+		Some((number - 1) as u8)
 	}
 
-	pub fn new(cell_name: &String) -> BoardCoordinates {
-		if cell_name.len() > 2 {
-			panic!("String must be exactly 2 characters long.");
+	// Convert a rank number to its row ('1'=0, '2'=1, ...)
+	fn rank_to_row(rank: &str) -> Option<u8> {
+		if rank.is_empty() || rank.starts_with('0') || !rank.chars().all(|c| c.is_digit(10)) {
+			return None;
 		}
 
-		BoardCoordinates {
-			col: BoardCoordinates::char_to_column(
-				match cell_name.chars().nth(0) {
-					Some(v) => v,
-					None => panic!("String must be at least 2 characters long")
-				}
-			),
-			row: BoardCoordinates::char_to_row(
-				match cell_name.chars().nth(1) {
-					Some(v) => v,
-					None => panic!("String must be at least 2 characters long")
-				}
-			),
+		match rank.parse::<u32>() {
+			Ok(number) if number <= 256 => Some((number - 1) as u8),
+			_ => None
+		}
+	}
+
+	// Coordinates from a square name such as 'e4', or 'j10' on larger boards
+	pub fn new(cell_name: &str) -> Result<BoardCoordinates, ChessError> {
+		let rank_start = cell_name.find(|c: char| !c.is_ascii_lowercase()).unwrap_or(cell_name.len());
+		let (file, rank) = cell_name.split_at(rank_start);
+
+		match (BoardCoordinates::file_to_column(file), BoardCoordinates::rank_to_row(rank)) {
+			(Some(col), Some(row)) => Ok(BoardCoordinates {
+				col: col,
+				row: row
+			}),
+			_ => Err(ChessError::InvalidSquareName(cell_name.to_string()))
 		}
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ChessBoardCell<T: Clone + Debug> {
 	coordinates: BoardCoordinates,
@@ -155,19 +286,19 @@ impl<T: Clone + Debug> ChessBoardCell<T> {
 		self.coordinates.to_string()
 	}
 
-	pub fn from_string(cell_name: String) -> ChessBoardCell<T> {
-		ChessBoardCell {
-			coordinates: BoardCoordinates::new(&cell_name),
+	pub fn from_string(cell_name: String) -> Result<ChessBoardCell<T>, ChessError> {
+		Ok(ChessBoardCell {
+			coordinates: try!(BoardCoordinates::new(&cell_name)),
 			contents: None
-		}
+		})
 	}
 
 	// Converting a string like c7 to numeric coordinates in a grid, containing a piece (or None)
-	pub fn from_string_with_contents(cell_name: String, contents: T) -> ChessBoardCell<T> {
-		ChessBoardCell {
-			coordinates: BoardCoordinates::new(&cell_name),
+	pub fn from_string_with_contents(cell_name: String, contents: T) -> Result<ChessBoardCell<T>, ChessError> {
+		Ok(ChessBoardCell {
+			coordinates: try!(BoardCoordinates::new(&cell_name)),
 			contents: Some(contents)
-		}
+		})
 	}
 
 	pub fn from_coordinates(coordinates: BoardCoordinates) -> ChessBoardCell<T> {
@@ -208,12 +339,13 @@ impl<'a> ChessBoardCell<ChessPiece> {
 
 #[allow(dead_code)]
 impl<T: Clone + Debug> ChessBoard<T> {
-	pub fn new (size: u8) -> ChessBoard<T> {
+	// An empty board `width` columns wide and `height` rows high
+	pub fn new (width: u8, height: u8) -> ChessBoard<T> {
 		
 		let mut columns: Vec<Vec<ChessBoardCell<T>>> = Vec::new();
-		for c in 0..size {
+		for c in 0..width {
 			let mut column: Vec<ChessBoardCell<T>> = Vec::new();
-			for r in 0..size {
+			for r in 0..height {
 				column.push(
 					ChessBoardCell::from_coordinates(
 						BoardCoordinates {
@@ -227,200 +359,318 @@ impl<T: Clone + Debug> ChessBoard<T> {
 		}
 
 		ChessBoard {
-			size: size,
+			width: width,
+			height: height,
 			columns: columns
 		}
 	}
 
-	pub fn get_size(&self) -> u8 {
-		self.size
+	pub fn get_width(&self) -> u8 {
+		self.width
+	}
+
+	pub fn get_height(&self) -> u8 {
+		self.height
+	}
+
+	// (width, height), the number of columns and rows
+	pub fn get_dimensions(&self) -> (u8, u8) {
+		(self.width, self.height)
+	}
+
+	// Coordinates of every cell on the board, column by column
+	pub fn get_all_coordinates(&self) -> Vec<BoardCoordinates> {
+		self.columns.iter()
+			.flat_map(|column| column.iter().map(|cell| cell.coordinates))
+			.collect()
 	}
 
-	pub fn get_contents_at_coordinates(&self, coordinates: &BoardCoordinates) -> Result<&Option<T>, &'static str> {
-		if self.size < coordinates.col || self.size < coordinates.row {
-			return Err("Invalid coordinates");
+	// The only place coordinates are checked against the size of the board
+	pub fn get_square(&self, coordinates: &BoardCoordinates) -> Result<Square, ChessError> {
+		if coordinates.col >= self.width || coordinates.row >= self.height {
+			return Err(ChessError::OutOfBounds(*coordinates));
 		}
 
-		match self.columns.get(coordinates.col as usize) {
-			Some(rows) => {
-				match rows.get(coordinates.row as usize) {
-					Some(cell) => {
-						return Ok(cell.get_contents())
-					},
-					None => {
-						return Err("Invalid coordinates");
-					}
-				}
-			},
-			None => { return Err("Invalid coordinates"); }
+		Ok(Square {
+			col: coordinates.col,
+			row: coordinates.row,
+			width: self.width,
+			height: self.height
+		})
+	}
+
+	// Every square of the board, column by column
+	pub fn get_all_squares(&self) -> Vec<Square> {
+		self.get_all_coordinates().iter()
+			.filter_map(|coordinates| self.get_square(coordinates).ok())
+			.collect()
+	}
+
+	// Whether `square` was made for a board of this size. Any square of one is on all the others.
+	fn has_square(&self, square: &Square) -> bool {
+		square.width == self.width && square.height == self.height
+	}
+
+	// The square `horzontal` columns and `vertical` rows away, if that is still on the board
+	pub fn offset_square(&self, square: &Square, horzontal: i8, vertical: i8) -> Option<Square> {
+		let col = square.col as i16 + horzontal as i16;
+		let row = square.row as i16 + vertical as i16;
+		if !self.has_square(square) || col < 0 || row < 0 || col >= self.width as i16 || row >= self.height as i16 {
+			return None;
+		}
+
+		self.get_square(&BoardCoordinates { col: col as u8, row: row as u8 }).ok()
+	}
+
+	pub fn get_cell(&self, square: &Square) -> Result<&ChessBoardCell<T>, ChessError> {
+		if !self.has_square(square) {
+			return Err(ChessError::OutOfBounds(square.get_coordinates()));
 		}
+
+		Ok(&self.columns[square.col as usize][square.row as usize])
+	}
+
+	pub fn get_square_contents(&self, square: &Square) -> Result<&Option<T>, ChessError> {
+		self.get_cell(square).map(|cell| cell.get_contents())
 	}
 
-	pub fn set_contents_at_coordinates(&mut self, coordinates: &BoardCoordinates, contents: Option<T>) {
-		if self.size < coordinates.col || self.size < coordinates.row {
-			panic!("Coordinates are out of bounds of the board");
+	pub fn set_square_contents(&mut self, square: &Square, contents: Option<T>) -> Result<(), ChessError> {
+		if !self.has_square(square) {
+			return Err(ChessError::OutOfBounds(square.get_coordinates()));
 		}
 
-		self.columns
-			.get_mut(coordinates.col as usize).unwrap()
-			.get_mut(coordinates.row as usize).unwrap()
-			.set_contents(contents);
+		self.columns[square.col as usize][square.row as usize].set_contents(contents);
+		Ok(())
+	}
+
+	// Moves whatever is on `start` to `end`, returning what was on `end` before
+	pub fn move_square_contents(&mut self, start: &Square, end: &Square) -> Result<Option<T>, ChessError> {
+		let contents = try!(self.get_square_contents(start)).clone();
+		let replaced = try!(self.get_square_contents(end)).clone();
+		try!(self.set_square_contents(start, None));
+		try!(self.set_square_contents(end, contents));
+		Ok(replaced)
+	}
+
+	pub fn get_contents_at_coordinates(&self, coordinates: &BoardCoordinates) -> Result<&Option<T>, ChessError> {
+		let square = try!(self.get_square(coordinates));
+		self.get_square_contents(&square)
+	}
+
+	pub fn set_contents_at_coordinates(&mut self, coordinates: &BoardCoordinates, contents: Option<T>) -> Result<(), ChessError> {
+		let square = try!(self.get_square(coordinates));
+		self.set_square_contents(&square, contents)
 	}
 
 	pub fn get_move_destination(&self, start_coordinates: &BoardCoordinates, horzontal: i8, vertical: i8) -> Option<&ChessBoardCell<T>> {
-		let final_col = start_coordinates.col as i8 + horzontal;
-		let final_row = start_coordinates.row as i8 + vertical;
+		self.get_square(start_coordinates).ok()
+			.and_then(|square| self.offset_square(&square, horzontal, vertical))
+			.and_then(|square| self.get_cell(&square).ok())
+	}
 
-		if final_row < 0 || final_col < 0 {
-			return None;
+	pub fn move_contents(&mut self, start_coordinates: &BoardCoordinates, end_coordinates: &BoardCoordinates) -> Result<(), ChessError>
+	{
+		let start = try!(self.get_square(start_coordinates));
+		let end = try!(self.get_square(end_coordinates));
+		if try!(self.get_square_contents(&start)).is_none() {
+			return Err(ChessError::EmptySquare(*start_coordinates));
 		}
 
-		match self.columns.iter().nth(final_col as usize) {
-			Some(rows) => {
-				return rows.iter().nth(final_row as usize);
-			}
-			None => {
-				return None;
-			}
+		try!(self.move_square_contents(&start, &end));
+		Ok(())
+	}
+}
+
+#[cfg(feature = "serialization")]
+impl<T: Clone + Debug> From<ChessBoard<T>> for SavedBoard<T> {
+	fn from(board: ChessBoard<T>) -> SavedBoard<T> {
+		let pieces = board.columns.into_iter()
+			.flat_map(|column| column.into_iter())
+			.filter_map(|cell| {
+				let coordinates = cell.coordinates;
+				cell.contents.map(|contents| (coordinates, contents))
+			})
+			.collect();
+
+		SavedBoard {
+			width: board.width,
+			height: board.height,
+			pieces: pieces
 		}
 	}
+}
 
-	pub fn move_contents(&mut self, start_coordinates: &BoardCoordinates, end_coordinates: &BoardCoordinates)
-	{
-		if start_coordinates.col > self.size || start_coordinates.row > self.size || 
-			end_coordinates.col > self.size || end_coordinates.row > self.size
-		{
-				panic!("Moving contents from or outside of board")
-		}
-		// Todo, figure out how to use Cell, RC, Refcell ect... 
-		// so that contents don't have to be cloned to do this
-		let contents = match self.get_contents_at_coordinates(start_coordinates) {
-			Ok(contents_at_coordinates) => {
-				contents_at_coordinates.clone()
-			},
-			Err(e) => { panic!("{}", e) }
-		};
-		self.set_contents_at_coordinates(start_coordinates, None);
-		self.set_contents_at_coordinates(end_coordinates, contents);
+#[cfg(feature = "serialization")]
+impl<T: Clone + Debug> TryFrom<SavedBoard<T>> for ChessBoard<T> {
+	type Error = ChessError;
+
+	fn try_from(saved: SavedBoard<T>) -> Result<ChessBoard<T>, ChessError> {
+		// Everything about a board counts back from its last row and column, so it needs at least one of each
+		if saved.width == 0 || saved.height == 0 {
+			return Err(ChessError::InvalidData(format!("A board can not be {}x{}", saved.width, saved.height)));
+		}
+
+		let mut board = ChessBoard::new(saved.width, saved.height);
+		for (coordinates, contents) in saved.pieces {
+			try!(board.set_contents_at_coordinates(&coordinates, Some(contents)));
+		}
+		Ok(board)
 	}
 }
 
+// White's back rank from the a-file, black's mirrors it across the board
+const BACK_RANK: [ChessPieceType; 8] = [
+	ChessPieceType::Rook,
+	ChessPieceType::Knight,
+	ChessPieceType::Bishop,
+	ChessPieceType::Queen,
+	ChessPieceType::King,
+	ChessPieceType::Bishop,
+	ChessPieceType::Knight,
+	ChessPieceType::Rook
+];
+
 impl ChessBoard<ChessPiece> {
-	pub fn fresh_game(&mut self, bottom_player_color: ChessPieceColor) {
-		for (i, r) in self.columns.iter_mut().enumerate() {
-			for board_cell in r.iter_mut() {
-				let contents = ChessBoard::get_contents_for_starting_coordinates(bottom_player_color, board_cell.get_coordinates());
+	// The standard starting position: white on rows 0 and 1 with the queen on d1, black on the last two
+	pub fn fresh_game(&mut self) {
+		let last_row = self.height - 1;
+		for column in self.columns.iter_mut() {
+			for board_cell in column.iter_mut() {
+				let contents = ChessBoard::get_contents_for_starting_coordinates(last_row, board_cell.get_coordinates());
 				board_cell.set_contents(contents);
 			}
 		}
 	}
 
-	fn get_contents_for_starting_coordinates(bottom_player_color: ChessPieceColor, coordinates: &BoardCoordinates) -> Option<ChessPiece> {
+	fn get_contents_for_starting_coordinates(last_row: u8, coordinates: &BoardCoordinates) -> Option<ChessPiece> {
 		let &BoardCoordinates{row, col} = coordinates;
-		match (col, row) {
-			(_, 1) => {
-				return Some(ChessPiece::new(ChessPieceType::Pawn, bottom_player_color));
-			},
-			(_, 6) => {
-				return Some(ChessPiece::new(ChessPieceType::Pawn, bottom_player_color.opposite_color()));
-			},
-			(0, 0) | (7, 0) => {
-				return Some(ChessPiece::new(ChessPieceType::Rook, bottom_player_color));	
-			},
-			(1, 0) | (6, 0) => {
-				return Some(ChessPiece::new(ChessPieceType::Knight, bottom_player_color));	
-			},
-			(2, 0) | (5, 0) => {
-				return Some(ChessPiece::new(ChessPieceType::Bishop, bottom_player_color));	
-			},
-			(3 , 0) => {
-				return Some(ChessPiece::new(ChessPieceType::King, bottom_player_color));	
-			},
-			(4, 0) => {
-				return Some(ChessPiece::new(ChessPieceType::Queen, bottom_player_color));	
-			},
-			(0, 7) | (7, 7) => {
-				return Some(ChessPiece::new(ChessPieceType::Rook, bottom_player_color.opposite_color()));	
-			},
-			(1 , 7) | (6, 7) => {
-				return Some(ChessPiece::new(ChessPieceType::Knight, bottom_player_color.opposite_color()));	
-			},
-			(2, 7) | (5, 7) => {
-				return Some(ChessPiece::new(ChessPieceType::Bishop, bottom_player_color.opposite_color()));	
-			},
-			(4 , 7) => {
-				return Some(ChessPiece::new(ChessPieceType::King, bottom_player_color.opposite_color()));	
-			},
-			(3, 7) => {
-				return Some(ChessPiece::new(ChessPieceType::Queen, bottom_player_color.opposite_color()));	
-			},
-			_ => { 
-				return None
-			}
+		let back_rank_piece = BACK_RANK.get(col as usize).cloned();
+		match row {
+			0 => back_rank_piece.map(|type_name| ChessPiece::new(type_name, ChessPieceColor::White)),
+			1 => Some(ChessPiece::new(ChessPieceType::Pawn, ChessPieceColor::White)),
+			_ if row == last_row - 1 => Some(ChessPiece::new(ChessPieceType::Pawn, ChessPieceColor::Black)),
+			_ if row == last_row => back_rank_piece.map(|type_name| ChessPiece::new(type_name, ChessPieceColor::Black)),
+			_ => None
 		}
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct ChessMove {
 	pub start_coordinates: BoardCoordinates,
-	pub end_coordinates: BoardCoordinates
+	pub end_coordinates: BoardCoordinates,
+	pub promotion: Option<ChessPieceType>, // What a pawn reaching the last rank becomes
+	pub castling_rook: Option<BoardCoordinates>, // Where the rook a king castles with stands
+	pub drop: Option<ChessPieceType> // A piece put on the board from the pocket, both squares being where it lands
 }
 
 impl ChessMove {
 	pub fn new(start_coordinates: &BoardCoordinates, end_coordinates: &BoardCoordinates) -> ChessMove {
 		ChessMove {
 			start_coordinates: start_coordinates.clone(),
-			end_coordinates: end_coordinates.clone()
+			end_coordinates: end_coordinates.clone(),
+			promotion: None,
+			castling_rook: None,
+			drop: None
+		}
+	}
+
+	// The king moving from `start_coordinates` to `end_coordinates`, with the rook at `rook_coordinates`
+	// jumping over it
+	pub fn castling(start_coordinates: &BoardCoordinates, end_coordinates: &BoardCoordinates, rook_coordinates: &BoardCoordinates) -> ChessMove {
+		ChessMove {
+			castling_rook: Some(*rook_coordinates),
+			..ChessMove::new(start_coordinates, end_coordinates)
+		}
+	}
+
+	// A piece of `type_name` taken from the pocket of the side to move and put on `coordinates`
+	pub fn drop(type_name: ChessPieceType, coordinates: &BoardCoordinates) -> ChessMove {
+		ChessMove {
+			drop: Some(type_name),
+			..ChessMove::new(coordinates, coordinates)
+		}
+	}
+
+	pub fn with_promotion(&self, promotion: ChessPieceType) -> ChessMove {
+		ChessMove {
+			promotion: Some(promotion),
+			..*self
+		}
+	}
+
+	// Castling towards the higher columns, i.e. the h-file on a standard board
+	pub fn is_king_side_castling(&self) -> bool {
+		match self.castling_rook {
+			Some(rook) => rook.col > self.start_coordinates.col,
+			None => false
 		}
 	}
 }
 
-impl Renderable for ChessBoard<ChessPiece> {
-	fn draw(&self, window: &PistonWindow) {
-		&self.columns.first().unwrap().first().unwrap().draw(window);
-		return;
-		for row in &self.columns {
-			for board_cell in row {
-				board_cell.draw(window);
-				return;
+// The pieces a pawn may be promoted to, in the order they are offered
+pub const PROMOTION_CHOICES: [ChessPieceType; 4] = [
+	ChessPieceType::Queen,
+	ChessPieceType::Rook,
+	ChessPieceType::Bishop,
+	ChessPieceType::Knight
+];
+
+// The board as text with the top row first, labelled with ranks and files. Pieces are Unicode
+// figurines, or ASCII letters with the alternate flag ('{:#}') for terminals without the glyphs.
+impl fmt::Display for ChessBoard<ChessPiece> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let empty = if f.alternate() { '.' } else { '\u{00B7}' };
+
+		for row in (0..self.height).rev() {
+			try!(write!(f, "{:>2} ", row as u32 + 1));
+			for col in 0..self.width {
+				let symbol = match self.columns[col as usize][row as usize].contents {
+					Some(ref piece) if f.alternate() => piece.to_letter(),
+					Some(ref piece) => piece.to_figurine(),
+					None => empty
+				};
+				try!(write!(f, " {}", symbol));
 			}
-		};
+			try!(writeln!(f, ""));
+		}
 
-		// Todo, also draw the boarders between cells?
+		try!(write!(f, "   "));
+		for col in 0..self.width {
+			try!(write!(f, " {}", BoardCoordinates { col: col, row: 0 }.get_file_name()));
+		}
+		writeln!(f, "")
 	}
 }
 
-impl Renderable for ChessBoardCell<ChessPiece> {
-	fn draw(&self, window: &PistonWindow) {
-	    match self.contents {
-	    	// Todo: Lookup ref keyword
-	    	Some(ref contents) => {
-	    		window.draw_2d(|c, gl| {
-	    			draw_chess_piece_sprite(window, contents, (200f64, 200f64));
-	    		})
-	    	},
-	    	None => {}
-	    }
+impl Renderable for ChessBoard<ChessPiece> {
+	fn draw(&self, backend: &mut DrawBackend, theme: &Theme) {
+		let layout = BoardLayout::window(self.get_dimensions(), false);
+		draw_board_squares(backend, theme, &layout);
+		draw_board_coordinates(backend, theme, &layout, theme.coordinate_font_size);
+		self.draw_pieces(backend, &layout, None);
+
+		// Todo, also draw the boarders between cells?
 	}
 }
 
-pub fn draw_chess_piece_sprite(window: &PistonWindow, piece: &ChessPiece, position: (f64, f64))
-{
-	let assets = ::find_folder::Search::ParentsThenKids(3, 3)
-	.for_folder("sprites").unwrap();
-
-	let tex = ::std::rc::Rc::new(::piston_window::Texture::from_path(
-        &mut *window.factory.borrow_mut(),
-        assets.join("0_bishop.png"),
-        ::piston_window::Flip::None,
-        &::piston_window::TextureSettings::new()
-    ).unwrap());
-
-    let mut sprite = ::sprite::Sprite::from_texture(tex.clone());
-    sprite.set_position(position.0, position.1);
-
-    window.draw_2d(|c, gl| {
-    	sprite.draw(c.transform, gl);
-    });
-}
\ No newline at end of file
+impl ChessBoard<ChessPiece> {
+	// Draws every piece on the board except the one at `hidden` (e.g. while it is being dragged)
+	pub fn draw_pieces(&self, backend: &mut DrawBackend, layout: &BoardLayout, hidden: Option<&BoardCoordinates>) {
+		for column in &self.columns {
+			for board_cell in column {
+				if hidden == Some(board_cell.get_coordinates()) {
+					continue;
+				}
+
+				match board_cell.contents {
+					Some(ref piece) => {
+						backend.draw_sprite(piece, layout.square_position(&board_cell.coordinates), layout.square_size / SQUARE_SIZE);
+					},
+					None => {}
+				}
+			}
+		}
+	}
+}
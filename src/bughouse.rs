@@ -0,0 +1,228 @@
+use board::*;
+use controller::ChessController;
+use game::{ChessGame, GameResult, WinReason, DrawReason};
+use piston_window::*;
+use theme::Theme;
+use variant::Rules;
+use view::{Renderable, DrawBackend, Font, ScaledBackend, BOARD_ORIGIN, SQUARE_SIZE, fit_square_size};
+
+const TEXT_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+const MUTED_TEXT_COLOR: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+const CLOCK_BACKGROUND_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const ACTIVE_CLOCK_BACKGROUND_COLOR: [f32; 4] = [0.75, 0.87, 0.7, 1.0];
+
+const CLOCK_WIDTH: f64 = 170.0;
+const CLOCK_HEIGHT: f64 = 40.0;
+const STRIP_MARGIN: f64 = 8.0;
+const POCKET_PIECE_SCALE: f64 = 0.5;
+
+// Space between the two boards, enough for the coordinates of the left one
+const BOARD_GAP: f64 = 80.0;
+
+// A game of bughouse: two boards played side by side, white on the first board partnering black
+// on the second. A piece captured on one board goes to the capturer's partner, who may drop it on
+// theirs. Each of the four players has their own clock and the match is over for everyone as soon
+// as either game ends.
+//
+// The second board is shown with black at the bottom, so partners sit next to each other.
+pub struct BughouseController {
+	boards: [ChessController; 2],
+	scale: f64, // Window pixels per pixel of the layout, less than 1.0 when it is shrunk to fit the window
+	cursor: (f64, f64) // In the layout
+}
+
+impl BughouseController {
+	pub fn new() -> BughouseController {
+		let first_game = BughouseController::new_game();
+		let second_offset = (first_game.get_board().get_width() as f64 * SQUARE_SIZE + BOARD_GAP, 0.0);
+		let mut second_game = BughouseController::new_game();
+		second_game.set_bottom_player_color(ChessPieceColor::Black);
+
+		BughouseController {
+			boards: [
+				ChessController::board_only(first_game, (0.0, 0.0)),
+				ChessController::board_only(second_game, second_offset)
+			],
+			scale: 1.0,
+			cursor: (0.0, 0.0)
+		}
+	}
+
+	fn new_game() -> ChessGame {
+		let mut game = ChessGame::new(ChessPieceColor::White);
+		game.initialize_pieces();
+		game.set_rules(Rules {
+			drops: true,
+			captures_to_partner: true,
+			..Rules::default()
+		});
+		game
+	}
+
+	// The game on the first (0) or second (1) board
+	pub fn get_game(&self, board: usize) -> &ChessGame {
+		self.boards[board].get_game()
+	}
+
+	// Turns both boards round, keeping partners next to each other
+	pub fn flip_boards(&mut self) {
+		for controller in self.boards.iter_mut() {
+			controller.flip_board();
+		}
+	}
+
+	// Width and height both boards and the strips around them take up with squares of SQUARE_SIZE
+	pub fn get_layout_size(&self) -> (f64, f64) {
+		let board = self.get_game(1).get_board();
+		(
+			self.get_board_x(1) + board.get_width() as f64 * SQUARE_SIZE + BOARD_ORIGIN.0,
+			self.get_strip_y(1, self.get_game(1).get_bottom_player_color()) + CLOCK_HEIGHT + STRIP_MARGIN
+		)
+	}
+
+	// Sizes the squares so both boards fit a window of `window_size`
+	pub fn fit_to_window(&mut self, window_size: (f64, f64)) {
+		self.scale = fit_square_size(self.get_layout_size(), window_size) / SQUARE_SIZE;
+		for controller in self.boards.iter_mut() {
+			controller.set_scale(self.scale);
+		}
+	}
+
+	pub fn event(&mut self, e: &PistonWindow) {
+		if let Some(position) = e.mouse_cursor_args() {
+			self.cursor = (position[0] / self.scale, position[1] / self.scale);
+		}
+
+		for board in 0..2 {
+			let moves_played = self.get_game(board).get_history().len();
+			self.boards[board].event(e);
+			if self.get_game(board).get_history().len() > moves_played {
+				self.pass_capture(board);
+			}
+		}
+
+		// Pieces are picked up after the boards have seen the click, which would otherwise drop them
+		if let Some(Button::Mouse(MouseButton::Left)) = e.press_args() {
+			if let Some((board, piece)) = self.pocket_piece_at_position(self.cursor) {
+				self.boards[board].pick_up_from_pocket(piece);
+			}
+		}
+
+		self.end_together();
+	}
+
+	// Hands the piece captured by the last move on `board` to the capturer's partner
+	fn pass_capture(&mut self, board: usize) {
+		let captured = self.get_game(board).get_history().last()
+			.and_then(|record| record.captured_as.map(|type_name| (record.piece.get_color(), type_name)));
+
+		if let Some((capturer, type_name)) = captured {
+			self.boards[1 - board].get_game_mut().add_to_pocket(capturer.opposite_color(), type_name);
+		}
+	}
+
+	// A win on one board is a loss for the partner of the loser on the other, and a draw is a draw for everyone
+	fn end_together(&mut self) {
+		for board in 0..2 {
+			let other_result = match self.get_game(board).get_result() {
+				Some(GameResult::Win(color, _)) => GameResult::Win(color.opposite_color(), WinReason::OtherBoard),
+				Some(GameResult::Draw(_)) => GameResult::Draw(DrawReason::OtherBoard),
+				None => { continue; }
+			};
+			self.boards[1 - board].get_game_mut().end(other_result);
+		}
+	}
+
+	// The second board starts where the first one ends, past the gap
+	fn get_board_x(&self, board: usize) -> f64 {
+		let first_width = self.get_game(0).get_board().get_width() as f64 * SQUARE_SIZE;
+		BOARD_ORIGIN.0 + board as f64 * (first_width + BOARD_GAP)
+	}
+
+	// Each player's clock and pocket are in a strip above or below their side of the board
+	fn get_strip_y(&self, board: usize, color: ChessPieceColor) -> f64 {
+		let game = self.get_game(board);
+		if color == game.get_bottom_player_color() {
+			BOARD_ORIGIN.1 + game.get_board().get_height() as f64 * SQUARE_SIZE + STRIP_MARGIN
+		} else {
+			BOARD_ORIGIN.1 - STRIP_MARGIN - CLOCK_HEIGHT
+		}
+	}
+
+	// Each kind of piece in a player's pocket with how many there are and where it is drawn, in a
+	// row beside the player's clock
+	fn get_pocket_layout(&self, board: usize, color: ChessPieceColor) -> Vec<(ChessPieceType, usize, (f64, f64))> {
+		let x = self.get_board_x(board) + CLOCK_WIDTH + 10.0;
+		let y = self.get_strip_y(board, color) + (CLOCK_HEIGHT - SQUARE_SIZE * POCKET_PIECE_SCALE) / 2.0;
+		let step = SQUARE_SIZE * POCKET_PIECE_SCALE * 1.5;
+
+		let mut layout: Vec<(ChessPieceType, usize, (f64, f64))> = Vec::new();
+		for &type_name in self.get_game(board).get_pocket(color) {
+			match layout.last_mut() {
+				Some(&mut (last_type, ref mut count, _)) if last_type == type_name => { *count += 1; },
+				_ => {
+					let position = (x + layout.len() as f64 * step, y);
+					layout.push((type_name, 1, position));
+				}
+			}
+		}
+
+		layout
+	}
+
+	// The board and pocketed piece under a window position, if any
+	fn pocket_piece_at_position(&self, position: (f64, f64)) -> Option<(usize, ChessPiece)> {
+		let size = SQUARE_SIZE * POCKET_PIECE_SCALE;
+		for board in 0..2 {
+			for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+				for (type_name, _, (x, y)) in self.get_pocket_layout(board, color) {
+					if position.0 >= x && position.0 < x + size && position.1 >= y && position.1 < y + size {
+						return Some((board, ChessPiece::new(type_name, color)));
+					}
+				}
+			}
+		}
+
+		None
+	}
+
+	fn draw_player(&self, backend: &mut DrawBackend, board: usize, color: ChessPieceColor) {
+		let game = self.get_game(board);
+		let (x, y) = (self.get_board_x(board), self.get_strip_y(board, color));
+		let is_running = game.get_result().is_none() && game.get_side_to_move() == color;
+		let background = if is_running { ACTIVE_CLOCK_BACKGROUND_COLOR } else { CLOCK_BACKGROUND_COLOR };
+
+		backend.draw_rect([x, y, CLOCK_WIDTH, CLOCK_HEIGHT], background);
+		backend.draw_text(&format!("{:?}", color), Font::Interface, 16, TEXT_COLOR, (x + 8.0, y + 26.0));
+		backend.draw_text(&game.get_clock(color).to_string(), Font::Interface, 22, TEXT_COLOR, (x + CLOCK_WIDTH - 75.0, y + 28.0));
+
+		let size = SQUARE_SIZE * POCKET_PIECE_SCALE;
+		for (type_name, count, (piece_x, piece_y)) in self.get_pocket_layout(board, color) {
+			backend.draw_sprite(&ChessPiece::new(type_name, color), (piece_x, piece_y), POCKET_PIECE_SCALE);
+			if count > 1 {
+				backend.draw_text(&count.to_string(), Font::Interface, 14, MUTED_TEXT_COLOR, (piece_x + size, piece_y + size));
+			}
+		}
+	}
+}
+
+impl Renderable for BughouseController {
+	fn draw(&self, backend: &mut DrawBackend, theme: &Theme) {
+		for board in 0..2 {
+			// Pockets first, so a piece dragged out of one is drawn over them. The boards scale themselves.
+			{
+				let mut backend = ScaledBackend::new(backend, self.scale);
+				self.draw_player(&mut backend, board, ChessPieceColor::White);
+				self.draw_player(&mut backend, board, ChessPieceColor::Black);
+			}
+			self.boards[board].draw(backend, theme);
+
+			if let Some(result) = self.get_game(board).get_result() {
+				let mut backend = ScaledBackend::new(backend, self.scale);
+				let x = self.get_board_x(board);
+				backend.draw_text(result.get_score(), Font::Interface, 18, TEXT_COLOR, (x, 30.0));
+				backend.draw_text(&result.to_string(), Font::Interface, 14, MUTED_TEXT_COLOR, (x + 70.0, 30.0));
+			}
+		}
+	}
+}
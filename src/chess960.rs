@@ -0,0 +1,75 @@
+use board::*;
+use error::ChessError;
+use game::ChessGame;
+use rand::{self, Rng};
+
+// Number of Chess960 starting positions, and the index of the one that is standard chess
+pub const CHESS960_POSITIONS: u16 = 960;
+pub const STANDARD_CHESS960_INDEX: u16 = 518;
+
+// Where the two knights go among the five squares left after the bishops and queen are placed
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+	(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)
+];
+
+// White's back rank, a-file first, for a Chess960 starting position numbered as by Scharnagl:
+// the index picks the light squared bishop, then the dark squared bishop, the queen and the
+// knights, and the king goes between the rooks on the three squares that are left.
+pub fn chess960_back_rank(index: u16) -> Result<[ChessPieceType; 8], ChessError> {
+	if index >= CHESS960_POSITIONS {
+		return Err(ChessError::InvalidFen(format!("There is no Chess960 position {}, they run from 0 to {}", index, CHESS960_POSITIONS - 1)));
+	}
+
+	let mut rank: [Option<ChessPieceType>; 8] = [None; 8];
+	let mut n = index as usize;
+
+	rank[(n % 4) * 2 + 1] = Some(ChessPieceType::Bishop);
+	n /= 4;
+	rank[(n % 4) * 2] = Some(ChessPieceType::Bishop);
+	n /= 4;
+
+	// The remaining pieces fill the empty squares from the a-file onwards
+	let place = |rank: &mut [Option<ChessPieceType>; 8], empty_index: usize, type_name: ChessPieceType| {
+		let col = (0..8).filter(|&col| rank[col].is_none()).nth(empty_index).unwrap();
+		rank[col] = Some(type_name);
+	};
+
+	place(&mut rank, n % 6, ChessPieceType::Queen);
+	n /= 6;
+
+	// The second knight goes first so that placing it does not move the first knight's square
+	let (first, second) = KNIGHT_PLACEMENTS[n];
+	place(&mut rank, second, ChessPieceType::Knight);
+	place(&mut rank, first, ChessPieceType::Knight);
+
+	for &type_name in &[ChessPieceType::Rook, ChessPieceType::King, ChessPieceType::Rook] {
+		place(&mut rank, 0, type_name);
+	}
+
+	let mut result = [ChessPieceType::Pawn; 8];
+	for (col, type_name) in rank.iter().enumerate() {
+		result[col] = type_name.unwrap();
+	}
+	Ok(result)
+}
+
+// The starting position numbered `index` in Forsyth-Edwards Notation, both sides able to castle
+pub fn chess960_fen(index: u16) -> Result<String, ChessError> {
+	let back_rank: String = try!(chess960_back_rank(index)).iter()
+		.map(|&type_name| ChessPiece::new(type_name, ChessPieceColor::Black).to_letter())
+		.collect();
+	Ok(format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", back_rank, back_rank.to_uppercase()))
+}
+
+pub fn random_chess960_index() -> u16 {
+	rand::thread_rng().gen_range(0, CHESS960_POSITIONS)
+}
+
+impl ChessGame {
+	// A Chess960 game from the starting position numbered `index`, 518 being the usual one
+	pub fn new_chess960(index: u16) -> Result<ChessGame, ChessError> {
+		let mut game = try!(ChessGame::from_fen(&try!(chess960_fen(index))));
+		game.set_chess960(true);
+		Ok(game)
+	}
+}
@@ -0,0 +1,447 @@
+use rust_chess::{ChessGame, ChessPieceColor, STARTING_FEN, Session, Variant, parse_pgn, search};
+use rust_chess::chess960::random_chess960_index;
+#[cfg(feature = "gui")]
+use rust_chess::gui::{run_gui, run_bughouse_gui};
+use rust_chess::session::autosave_path;
+use rust_chess::svg::{SvgOptions, board_to_svg, save_svg};
+use rust_chess::terminal::play_in_terminal;
+use rust_chess::uci::run_uci;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+// Exit codes
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1; // The command ran but its input was invalid, e.g. an illegal move
+pub const EXIT_USAGE: i32 = 2; // The command line itself was wrong
+
+const DEFAULT_ANALYSIS_DEPTH: u32 = 3;
+
+const USAGE: &'static str = "Usage: rust_chess [COMMAND] [ARGUMENTS]
+
+Commands:
+  gui [GAME]                             Play in a window (the default, needs the \"gui\" feature)
+  tui [GAME] [--ascii]                   Play in the terminal by typing moves
+  bughouse                               Play bughouse on two boards in a window (needs the \"gui\" feature)
+  uci                                    Run as an engine speaking the Universal Chess Interface
+  perft FEN DEPTH [--divide] [--variant FILE]
+                                         Count the move sequences DEPTH plies deep, by the rules of a variant
+  fen-to-svg FEN [FILE] [--flip] [--no-coordinates]
+                                         Draw a position as SVG, to FILE or standard output
+  pgn-validate FILE                      Check that every move in a PGN file is legal ('-' reads standard input)
+  analyse FEN [--depth N]                Search a position for the best move
+  help                                   Show this message
+
+FEN may also be 'startpos' for the standard starting position.
+
+GAME picks what to play, a new game when left out:
+  --fen FEN                              A new game from a position
+  --chess960 N                           A new Chess960 game from starting position N (0 to 959) or 'random'
+  --variant FILE                         A new game of a variant, e.g. resources/variants/capablanca.variant
+  --load FILE                            A game saved with 'save' in the terminal
+  --continue                             The game that was open when rust_chess last closed
+  --white NAME, --black NAME             The players' names
+  --flip                                 Show black's side at the bottom of the window (F turns it round)
+
+Games are saved to continue later when the window or terminal game is closed, and with the S key in
+the window. Saving needs the \"serialization\" feature.
+
+Exit status is 0 on success, 1 when the input is invalid and 2 when the command line is wrong.";
+
+// Runs the command named by the arguments (without the program name) and returns the exit code
+pub fn run(arguments: &[String]) -> i32 {
+	let mut arguments: Vec<String> = arguments.to_vec();
+	if take_flag(&mut arguments, "--help") || take_flag(&mut arguments, "-h") {
+		println!("{}", USAGE);
+		return EXIT_SUCCESS;
+	}
+
+	if arguments.is_empty() {
+		return run_gui_command(arguments);
+	}
+
+	let command = arguments.remove(0);
+	match &command[..] {
+		"gui" => run_gui_command(arguments),
+		"tui" => run_tui_command(arguments),
+		"bughouse" => run_bughouse_command(arguments),
+		"uci" => {
+			if !arguments.is_empty() {
+				return usage_error("uci takes no arguments");
+			}
+			run_uci();
+			EXIT_SUCCESS
+		},
+		"perft" => run_perft_command(arguments),
+		"fen-to-svg" => run_svg_command(arguments),
+		"pgn-validate" => run_pgn_validate_command(arguments),
+		"analyse" | "analyze" => run_analyse_command(arguments),
+		"help" => {
+			println!("{}", USAGE);
+			EXIT_SUCCESS
+		},
+		other => usage_error(&format!("Unknown command '{}'", other))
+	}
+}
+
+fn run_gui_command(mut arguments: Vec<String>) -> i32 {
+	let session = match take_session_options(&mut arguments) {
+		Ok(session) => session,
+		Err(code) => { return code; }
+	};
+	if !arguments.is_empty() {
+		return usage_error(&format!("Unexpected argument '{}'", arguments[0]));
+	}
+
+	start_gui(session)
+}
+
+#[cfg(feature = "gui")]
+fn start_gui(session: Session) -> i32 {
+	autosave(&run_gui(session));
+	EXIT_SUCCESS
+}
+
+#[cfg(not(feature = "gui"))]
+fn start_gui(_session: Session) -> i32 {
+	failure("This build has no window, rebuild with the \"gui\" feature or use 'tui'")
+}
+
+fn run_bughouse_command(arguments: Vec<String>) -> i32 {
+	if !arguments.is_empty() {
+		return usage_error(&format!("Unexpected argument '{}'", arguments[0]));
+	}
+
+	start_bughouse()
+}
+
+#[cfg(feature = "gui")]
+fn start_bughouse() -> i32 {
+	run_bughouse_gui(None);
+	EXIT_SUCCESS
+}
+
+#[cfg(not(feature = "gui"))]
+fn start_bughouse() -> i32 {
+	failure("This build has no window, rebuild with the \"gui\" feature")
+}
+
+fn run_tui_command(mut arguments: Vec<String>) -> i32 {
+	let ascii = take_flag(&mut arguments, "--ascii");
+	let mut session = match take_session_options(&mut arguments) {
+		Ok(session) => session,
+		Err(code) => { return code; }
+	};
+	if !arguments.is_empty() {
+		return usage_error(&format!("Unexpected argument '{}'", arguments[0]));
+	}
+
+	if ascii {
+		session.ascii = true;
+	}
+	autosave(&play_in_terminal(session));
+	EXIT_SUCCESS
+}
+
+// Keeps the session for --continue. Failing to save is reported but does not fail the command,
+// the game itself went fine.
+#[cfg(feature = "serialization")]
+fn autosave(session: &Session) {
+	if let Some(path) = autosave_path() {
+		if let Err(e) = session.save(&path) {
+			let _ = writeln!(io::stderr(), "Could not save the game: {}", e);
+		}
+	}
+}
+
+#[cfg(not(feature = "serialization"))]
+fn autosave(_session: &Session) {}
+
+fn run_perft_command(mut arguments: Vec<String>) -> i32 {
+	let divide = take_flag(&mut arguments, "--divide");
+	let variant = match take_option(&mut arguments, "--variant") {
+		Some(path) => match Variant::load(Path::new(&path)) {
+			Ok(variant) => Some(variant),
+			Err(e) => { return failure(&e); }
+		},
+		None => None
+	};
+	if arguments.len() != 2 {
+		return usage_error("perft needs a FEN and a depth");
+	}
+
+	// The position is read with the variant's pieces and played by its rules, 'startpos' being its start
+	let game = match variant {
+		Some(variant) => {
+			let fen = if arguments[0] == "startpos" { variant.start.clone() } else { arguments[0].clone() };
+			let mut game = match ChessGame::from_fen_with_pieces(&fen, variant.pieces.clone()) {
+				Ok(game) => game,
+				Err(e) => { return failure(&e.to_string()); }
+			};
+			game.set_rules(variant.rules);
+			game
+		},
+		None => match parse_fen_argument(&arguments[0]) {
+			Ok(game) => game,
+			Err(code) => { return code; }
+		}
+	};
+	let depth = match arguments[1].parse::<u32>() {
+		Ok(depth) => depth,
+		Err(_) => { return usage_error(&format!("Invalid depth '{}'", arguments[1])); }
+	};
+
+	if divide {
+		let mut total = 0;
+		for (chess_move, count) in game.perft_divide(depth) {
+			println!("{}: {}", game.get_coordinate_notation(&chess_move), count);
+			total += count;
+		}
+		println!("");
+		println!("Nodes: {}", total);
+	} else {
+		println!("Nodes: {}", game.perft(depth));
+	}
+
+	EXIT_SUCCESS
+}
+
+fn run_svg_command(mut arguments: Vec<String>) -> i32 {
+	let mut options = SvgOptions::default();
+	options.flipped = take_flag(&mut arguments, "--flip");
+	options.coordinates = !take_flag(&mut arguments, "--no-coordinates");
+	if arguments.len() < 1 || arguments.len() > 2 {
+		return usage_error("fen-to-svg needs a FEN and optionally an output file");
+	}
+
+	let game = match parse_fen_argument(&arguments[0]) {
+		Ok(game) => game,
+		Err(code) => { return code; }
+	};
+
+	match arguments.get(1) {
+		Some(path) => match save_svg(game.get_board(), &options, Path::new(path)) {
+			Ok(_) => EXIT_SUCCESS,
+			Err(e) => failure(&e)
+		},
+		None => {
+			print!("{}", board_to_svg(game.get_board(), &options));
+			EXIT_SUCCESS
+		}
+	}
+}
+
+fn run_pgn_validate_command(arguments: Vec<String>) -> i32 {
+	if arguments.len() != 1 {
+		return usage_error("pgn-validate needs a PGN file");
+	}
+
+	let mut text = String::new();
+	let read = if arguments[0] == "-" {
+		io::stdin().read_to_string(&mut text)
+	} else {
+		File::open(&arguments[0]).and_then(|mut file| file.read_to_string(&mut text))
+	};
+	if let Err(e) = read {
+		return failure(&format!("Could not read {}: {}", arguments[0], e));
+	}
+
+	let games = match parse_pgn(&text) {
+		Ok(games) => games,
+		Err(e) => { return failure(&e.to_string()); }
+	};
+	if games.is_empty() {
+		return failure("No games found");
+	}
+
+	let mut all_valid = true;
+	for (i, pgn_game) in games.iter().enumerate() {
+		let players = format!(
+			"{} - {}",
+			pgn_game.get_tag("White").unwrap_or("?"),
+			pgn_game.get_tag("Black").unwrap_or("?")
+		);
+		match pgn_game.replay() {
+			Ok(game) => {
+				let result = pgn_game.result.clone().unwrap_or("*".to_string());
+				println!("Game {} ({}): ok, {} moves, {}", i + 1, players, pgn_game.moves.len(), result);
+
+				// A game ending in mate or stalemate should say so in its result
+				if let Some(game_result) = game.get_result() {
+					if game_result.get_score() != result {
+						println!("  warning: the final position is {} but the result is {}", game_result.get_score(), result);
+					}
+				}
+			},
+			Err(e) => {
+				println!("Game {} ({}): {}", i + 1, players, e);
+				all_valid = false;
+			}
+		}
+	}
+
+	if all_valid { EXIT_SUCCESS } else { EXIT_FAILURE }
+}
+
+fn run_analyse_command(mut arguments: Vec<String>) -> i32 {
+	let depth = match take_option(&mut arguments, "--depth") {
+		Some(depth) => match depth.parse::<u32>() {
+			Ok(depth) if depth > 0 => depth,
+			_ => { return usage_error(&format!("Invalid depth '{}'", depth)); }
+		},
+		None => DEFAULT_ANALYSIS_DEPTH
+	};
+	if arguments.len() != 1 {
+		return usage_error("analyse needs a FEN");
+	}
+
+	let game = match parse_fen_argument(&arguments[0]) {
+		Ok(game) => game,
+		Err(code) => { return code; }
+	};
+
+	let result = search(&game, depth);
+	let best_move = match result.best_move {
+		Some(chess_move) => chess_move,
+		None => {
+			let outcome = if game.is_in_check(game.get_side_to_move()) { "checkmate" } else { "stalemate" };
+			println!("No legal moves: {}", outcome);
+			return EXIT_SUCCESS;
+		}
+	};
+
+	// Play the line out on a copy to write it with check marks and move numbers
+	let mut line = game.clone();
+	let mut variation: Vec<String> = Vec::new();
+	for (i, chess_move) in result.principal_variation.iter().enumerate() {
+		let number = line.get_fullmove_number();
+		match line.get_side_to_move() {
+			ChessPieceColor::White => variation.push(format!("{}.", number)),
+			ChessPieceColor::Black if i == 0 => variation.push(format!("{}...", number)),
+			ChessPieceColor::Black => {}
+		}
+		if line.move_piece(chess_move).is_err() {
+			break;
+		}
+		variation.push(line.get_history().last().unwrap().notation.clone());
+	}
+
+	let score = match result.get_mate_in() {
+		Some(moves) => format!("mate in {}", moves),
+		None => format!("{:+.2}", result.score as f64 / 100.0)
+	};
+	println!("Best move: {} ({})", game.get_algebraic_notation(&best_move), game.get_coordinate_notation(&best_move));
+	println!("Score: {} for {:?}", score, game.get_side_to_move());
+	println!("Line: {}", variation.join(" "));
+	println!("Depth: {}, nodes: {}", depth, result.nodes);
+
+	EXIT_SUCCESS
+}
+
+// The session for --load or --continue, or a new one around the game for --fen, --chess960 or
+// --variant, with the names from --white and --black and the board turned round for --flip
+fn take_session_options(arguments: &mut Vec<String>) -> Result<Session, i32> {
+	let load = take_option(arguments, "--load");
+	let resume = take_flag(arguments, "--continue");
+	let white_name = take_option(arguments, "--white");
+	let black_name = take_option(arguments, "--black");
+	let flip = take_flag(arguments, "--flip");
+
+	let sources = arguments.iter()
+		.filter(|argument| *argument == "--fen" || *argument == "--chess960" || *argument == "--variant")
+		.count() + load.iter().count() + resume as usize;
+	if sources > 1 {
+		return Err(usage_error("Only one of --fen, --chess960, --variant, --load and --continue can be given"));
+	}
+
+	let mut session = if resume {
+		match autosave_path() {
+			Some(path) => try!(Session::load(&path).map_err(|e| failure(&e.to_string()))),
+			None => { return Err(failure("There is no home folder to continue a game from")); }
+		}
+	} else if let Some(path) = load {
+		try!(Session::load(Path::new(&path)).map_err(|e| failure(&e.to_string())))
+	} else {
+		Session::new(try!(take_game_option(arguments)))
+	};
+
+	if let Some(name) = white_name {
+		session.white_name = name;
+	}
+	if let Some(name) = black_name {
+		session.black_name = name;
+	}
+	if flip {
+		session.game.set_bottom_player_color(ChessPieceColor::Black);
+	}
+	Ok(session)
+}
+
+// The game for --fen, --chess960 or --variant, or the usual new game
+fn take_game_option(arguments: &mut Vec<String>) -> Result<ChessGame, i32> {
+	if let Some(path) = take_option(arguments, "--variant") {
+		let variant = try!(Variant::load(Path::new(&path)).map_err(|e| failure(&e)));
+		println!("{}", variant.name);
+		return ChessGame::from_variant(&variant).map_err(|e| failure(&e.to_string()));
+	}
+
+	if let Some(index) = take_option(arguments, "--chess960") {
+		let index = match &index[..] {
+			"random" => random_chess960_index(),
+			number => try!(number.parse::<u16>().map_err(|_| usage_error(&format!("Invalid Chess960 position '{}'", number))))
+		};
+		println!("Chess960 position {}", index);
+		return ChessGame::new_chess960(index).map_err(|e| failure(&e.to_string()));
+	}
+
+	match take_option(arguments, "--fen") {
+		// Only positions that could come about in a game can be played from
+		Some(fen) => {
+			let game = try!(parse_fen_argument(&fen));
+			try!(game.validate_position().map_err(|e| failure(&e.to_string())));
+			Ok(game)
+		},
+		None => {
+			let mut game = ChessGame::new(ChessPieceColor::White);
+			game.initialize_pieces();
+			Ok(game)
+		}
+	}
+}
+
+fn parse_fen_argument(fen: &str) -> Result<ChessGame, i32> {
+	let fen = if fen == "startpos" { STARTING_FEN } else { fen };
+	ChessGame::from_fen(fen).map_err(|e| failure(&e.to_string()))
+}
+
+// Removes `name` from the arguments, returning whether it was there
+fn take_flag(arguments: &mut Vec<String>, name: &str) -> bool {
+	match arguments.iter().position(|argument| argument == name) {
+		Some(i) => {
+			arguments.remove(i);
+			true
+		},
+		None => false
+	}
+}
+
+// Removes `name` and the value after it from the arguments, returning the value
+fn take_option(arguments: &mut Vec<String>, name: &str) -> Option<String> {
+	match arguments.iter().position(|argument| argument == name) {
+		Some(i) if i + 1 < arguments.len() => {
+			arguments.remove(i);
+			Some(arguments.remove(i))
+		},
+		_ => None
+	}
+}
+
+fn usage_error(message: &str) -> i32 {
+	let _ = writeln!(io::stderr(), "{}\n\n{}", message, USAGE);
+	EXIT_USAGE
+}
+
+fn failure(message: &str) -> i32 {
+	let _ = writeln!(io::stderr(), "{}", message);
+	EXIT_FAILURE
+}
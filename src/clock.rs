@@ -0,0 +1,52 @@
+use std::fmt;
+
+// A single player's chess clock, counting down in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct ChessClock {
+	remaining: f64,
+	increment: f64
+}
+
+#[allow(dead_code)]
+impl ChessClock {
+	pub fn new(initial_seconds: f64, increment_seconds: f64) -> ChessClock {
+		ChessClock {
+			remaining: initial_seconds,
+			increment: increment_seconds
+		}
+	}
+
+	pub fn get_remaining(&self) -> f64 {
+		self.remaining
+	}
+
+	pub fn get_increment(&self) -> f64 {
+		self.increment
+	}
+
+	pub fn tick(&mut self, seconds: f64) {
+		self.remaining = (self.remaining - seconds).max(0.0);
+	}
+
+	// Called when the owner of the clock completes a move
+	pub fn add_increment(&mut self) {
+		self.remaining += self.increment;
+	}
+
+	pub fn is_flagged(&self) -> bool {
+		self.remaining <= 0.0
+	}
+}
+
+// Formats as m:ss, with tenths of a second once under ten seconds
+impl fmt::Display for ChessClock {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.remaining < 10.0 {
+			write!(f, "0:{:04.1}", self.remaining)
+		} else {
+			let seconds = self.remaining as u64;
+			write!(f, "{}:{:02}", seconds / 60, seconds % 60)
+		}
+	}
+}
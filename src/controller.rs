@@ -0,0 +1,253 @@
+use board::*;
+use game::ChessGame;
+use panel::{SidePanel, PANEL_MARGIN, PANEL_WIDTH};
+use promotion::PromotionPicker;
+use theme::Theme;
+use variant::WinCondition;
+use view::{Renderable, DrawBackend, BoardLayout, OffsetBackend, ScaledBackend, SQUARE_SIZE, board_layout_size, draw_board_squares,
+	draw_board_coordinates, fit_square_size, highlight_square, mark_square};
+use piston_window::*;
+
+// Turns mouse input in the window into moves on a ChessGame
+#[derive(Debug)]
+pub struct ChessController {
+	game: ChessGame,
+	offset: (f64, f64), // How far the board is moved from BOARD_ORIGIN, for windows with more than one
+	side_panel: bool, // Whether the move list, clocks and pockets are drawn beside the board
+	scale: f64, // Window pixels per pixel of the layout, less than 1.0 when it is shrunk to fit the window
+	cursor: (f64, f64), // In the layout, relative to the offset
+	dragged_from: Option<BoardCoordinates>,
+	dragged_drop: Option<ChessPiece>, // A piece being dragged out of a pocket
+	promotion_picker: Option<PromotionPicker>
+}
+
+impl ChessController {
+	pub fn new(game: ChessGame) -> ChessController {
+		ChessController {
+			game: game,
+			offset: (0.0, 0.0),
+			side_panel: true,
+			scale: 1.0,
+			cursor: (0.0, 0.0),
+			dragged_from: None,
+			dragged_drop: None,
+			promotion_picker: None
+		}
+	}
+
+	// Just the board, moved by `offset`, for a window that draws the clocks and pockets itself
+	pub fn board_only(game: ChessGame, offset: (f64, f64)) -> ChessController {
+		ChessController {
+			offset: offset,
+			side_panel: false,
+			..ChessController::new(game)
+		}
+	}
+
+	pub fn get_game(&self) -> &ChessGame {
+		&self.game
+	}
+
+	pub fn get_game_mut(&mut self) -> &mut ChessGame {
+		&mut self.game
+	}
+
+	// Starts dragging a piece out of a pocket, if it belongs to the side to move
+	pub fn pick_up_from_pocket(&mut self, piece: ChessPiece) {
+		if piece.get_color() == self.game.get_side_to_move() && self.game.get_pocket(piece.get_color()).contains(&piece.get_type()) {
+			self.dragged_from = None;
+			self.dragged_drop = Some(piece);
+		}
+	}
+
+	pub fn into_game(self) -> ChessGame {
+		self.game
+	}
+
+	// Width and height the board and panel take up with squares of SQUARE_SIZE
+	pub fn get_layout_size(&self) -> (f64, f64) {
+		let panel_width = if self.side_panel { PANEL_MARGIN * 2.0 + PANEL_WIDTH } else { 0.0 };
+		board_layout_size(self.game.get_board().get_dimensions(), panel_width)
+	}
+
+	// Where the board is drawn, before the offset and scale are applied
+	fn get_layout(&self) -> BoardLayout {
+		BoardLayout::window(self.game.get_board().get_dimensions(), self.game.is_flipped())
+	}
+
+	// Sizes the squares so the board and panel fit a window of `window_size`
+	pub fn fit_to_window(&mut self, window_size: (f64, f64)) {
+		self.scale = fit_square_size(self.get_layout_size(), window_size) / SQUARE_SIZE;
+	}
+
+	// For a controller drawn inside a larger layout that has been fitted to the window
+	pub fn set_scale(&mut self, scale: f64) {
+		self.scale = scale;
+	}
+
+	// Turns the board round to show the other side at the bottom
+	pub fn flip_board(&mut self) {
+		let bottom_color = self.game.get_bottom_player_color();
+		self.game.set_bottom_player_color(bottom_color.opposite_color());
+	}
+
+	pub fn event(&mut self, e: &PistonWindow) {
+		if let Some(args) = e.update_args() {
+			self.game.update(args.dt);
+		}
+
+		if let Some(position) = e.mouse_cursor_args() {
+			self.cursor = (position[0] / self.scale - self.offset.0, position[1] / self.scale - self.offset.1);
+		}
+
+		match e.press_args() {
+			Some(Button::Mouse(MouseButton::Left)) => { self.on_left_press(); },
+			// Right clicking backs out of a drag or a promotion, returning the pawn
+			Some(Button::Mouse(MouseButton::Right)) => {
+				self.dragged_from = None;
+				self.dragged_drop = None;
+				self.promotion_picker = None;
+			},
+			_ => {}
+		}
+
+		if let Some(Button::Mouse(MouseButton::Left)) = e.release_args() {
+			self.on_left_release();
+		}
+	}
+
+	fn on_left_press(&mut self) {
+		if self.promotion_picker.is_some() {
+			let picker = self.promotion_picker.take().unwrap();
+			// Clicking anywhere other than one of the choices cancels the promotion
+			if let Some(piece_type) = picker.choice_at_position(self.cursor) {
+				let chess_move = match piece_type {
+					ChessPieceType::Pawn => *picker.get_move(),
+					_ => picker.get_move().with_promotion(piece_type)
+				};
+				// A move the game rejects simply leaves the pawn where it was
+				let _ = self.game.move_piece(&chess_move);
+			}
+			return;
+		}
+
+		self.dragged_from = match self.get_layout().square_at_position(self.cursor) {
+			Some(coordinates) => {
+				match self.game.get_board().get_contents_at_coordinates(&coordinates) {
+					Ok(&Some(..)) => Some(coordinates),
+					_ => None
+				}
+			},
+			None => None
+		};
+
+		// Only the side to move may pick up one of its pieces in hand
+		self.dragged_drop = None;
+		if self.side_panel {
+			if let Some(piece) = SidePanel::new(&self.game).pocket_piece_at_position(self.cursor) {
+				self.pick_up_from_pocket(piece);
+			}
+		}
+	}
+
+	fn on_left_release(&mut self) {
+		let layout = self.get_layout();
+		let end_coordinates = layout.square_at_position(self.cursor);
+
+		if let Some(piece) = self.dragged_drop.take() {
+			if let Some(end_coordinates) = end_coordinates {
+				// A drop the game rejects simply leaves the piece in the pocket
+				let _ = self.game.move_piece(&ChessMove::drop(piece.get_type(), &end_coordinates));
+			}
+			return;
+		}
+
+		let start_coordinates = match self.dragged_from.take() {
+			Some(coordinates) => coordinates,
+			None => { return; }
+		};
+		let end_coordinates = match end_coordinates {
+			Some(coordinates) => coordinates,
+			None => { return; }
+		};
+
+		let chess_move = self.game.find_legal_move(&ChessMove::new(&start_coordinates, &end_coordinates)).unwrap_or(None);
+
+		match chess_move {
+			Some(chess_move) if self.game.is_promotion_move(&chess_move) => {
+				let color = match self.game.get_board().get_contents_at_coordinates(&start_coordinates) {
+					Ok(&Some(piece)) => piece.get_color(),
+					_ => { return; }
+				};
+				// Where promoting is optional the pawn itself is offered last, for staying a pawn
+				let mut choices = self.game.get_rules().promotion_pieces.clone();
+				if self.game.is_promotion_optional(&chess_move) {
+					choices.push(ChessPieceType::Pawn);
+				}
+				self.promotion_picker = Some(PromotionPicker::new(chess_move, color, choices, layout));
+			},
+			Some(chess_move) => {
+				let _ = self.game.move_piece(&chess_move);
+			},
+			None => {}
+		}
+	}
+}
+
+impl Renderable for ChessController {
+	fn draw(&self, backend: &mut DrawBackend, theme: &Theme) {
+		let mut backend = ScaledBackend::new(backend, self.scale);
+		let mut backend = OffsetBackend::new(&mut backend, self.offset);
+		let backend = &mut backend;
+		let board = self.game.get_board();
+		let layout = self.get_layout();
+		draw_board_squares(backend, theme, &layout);
+		draw_board_coordinates(backend, theme, &layout, theme.coordinate_font_size);
+
+		if self.game.get_rules().win_conditions.contains(&WinCondition::KingOfTheHill) {
+			for coordinates in board.get_all_coordinates().iter().filter(|coordinates| self.game.is_hill_square(coordinates)) {
+				highlight_square(backend, &layout, coordinates, theme.hill_highlight);
+			}
+		}
+		if let Some(record) = self.game.get_history().last() {
+			highlight_square(backend, &layout, &record.chess_move.start_coordinates, theme.last_move_highlight);
+			highlight_square(backend, &layout, &record.chess_move.end_coordinates, theme.last_move_highlight);
+		}
+		if let Some(coordinates) = self.dragged_from {
+			highlight_square(backend, &layout, &coordinates, theme.selected_highlight);
+		}
+
+		board.draw_pieces(backend, &layout, self.dragged_from.as_ref());
+
+		if let Some(coordinates) = self.dragged_from {
+			for chess_move in self.game.get_legal_moves(&coordinates).unwrap_or(Vec::new()) {
+				mark_square(backend, &layout, &chess_move.end_coordinates, theme.legal_move_highlight);
+			}
+		}
+
+		if let Some(piece) = self.dragged_drop {
+			for chess_move in self.game.get_drop_moves().iter().filter(|chess_move| chess_move.drop == Some(piece.get_type())) {
+				mark_square(backend, &layout, &chess_move.end_coordinates, theme.legal_move_highlight);
+			}
+		}
+
+		if self.side_panel {
+			SidePanel::new(&self.game).draw(backend, theme);
+		}
+
+		// The dragged piece follows the cursor, centred on it, drawn over the panel so a piece taken
+		// from the pocket stays in view
+		let dragged = match self.dragged_from {
+			Some(coordinates) => board.get_contents_at_coordinates(&coordinates).ok().and_then(|contents| *contents),
+			None => self.dragged_drop
+		};
+		if let Some(piece) = dragged {
+			let position = (self.cursor.0 - SQUARE_SIZE / 2.0, self.cursor.1 - SQUARE_SIZE / 2.0);
+			backend.draw_sprite(&piece, position, 1.0);
+		}
+
+		if let Some(ref picker) = self.promotion_picker {
+			picker.draw(backend, theme);
+		}
+	}
+}
@@ -0,0 +1,84 @@
+use board::*;
+use game::ChessGame;
+use image::{ColorType, RgbaImage};
+use image::png::PNGEncoder;
+use software_backend::SoftwareBackend;
+use std::path::Path;
+use theme::Theme;
+use view::{BoardLayout, SQUARE_SIZE, draw_board_coordinates, draw_board_squares, draw_square_arrow, highlight_square};
+
+const ARROW_COLOR: [f32; 4] = [0.08, 0.47, 0.13, 0.8];
+
+// Room left around the board for the coordinates, in the same units as SQUARE_SIZE
+const COORDINATE_MARGIN: f64 = 24.0;
+
+// How a position diagram is drawn
+#[derive(Debug, Clone)]
+pub struct DiagramOptions {
+	pub size: u32, // Width of the image in pixels, the height follows the shape of the board
+	pub bottom_color: ChessPieceColor, // Whose side of the board is at the bottom
+	pub coordinates: bool,
+	pub highlights: Vec<BoardCoordinates>,
+	pub arrows: Vec<(BoardCoordinates, BoardCoordinates)>,
+	pub theme: Theme
+}
+
+impl Default for DiagramOptions {
+	fn default() -> DiagramOptions {
+		DiagramOptions {
+			size: 400,
+			bottom_color: ChessPieceColor::White,
+			coordinates: true,
+			highlights: Vec::new(),
+			arrows: Vec::new(),
+			theme: Theme::default()
+		}
+	}
+}
+
+// Draws the position of `game` without a window, loading sprites and fonts from `resources`
+pub fn render_diagram(game: &ChessGame, options: &DiagramOptions, resources: &Path) -> Result<RgbaImage, String> {
+	let board = game.get_board();
+	let theme = &options.theme;
+	let margin = if options.coordinates { COORDINATE_MARGIN } else { 0.0 };
+	let layout = BoardLayout::diagram(board.get_dimensions(), SQUARE_SIZE, margin, options.bottom_color == ChessPieceColor::Black);
+
+	let (total_width, total_height) = layout.get_total_size();
+	let scale = options.size as f64 / total_width;
+	let height = (total_height * scale).round() as u32;
+	let mut backend = try!(SoftwareBackend::new(options.size, height, theme, resources));
+	backend.clear([1.0, 1.0, 1.0, 1.0]);
+	backend.set_transform(scale, (0.0, 0.0));
+
+	draw_board_squares(&mut backend, theme, &layout);
+	for coordinates in &options.highlights {
+		highlight_square(&mut backend, &layout, coordinates, theme.last_move_highlight);
+	}
+	// A little larger than in the window, as the diagram is usually shrunk to fit `size`
+	if options.coordinates {
+		draw_board_coordinates(&mut backend, theme, &layout, theme.coordinate_font_size + 4);
+	}
+	board.draw_pieces(&mut backend, &layout, None);
+	for &(from, to) in &options.arrows {
+		draw_square_arrow(&mut backend, &layout, &from, &to, ARROW_COLOR);
+	}
+
+	Ok(backend.into_image())
+}
+
+// The diagram encoded as a PNG file in memory
+pub fn render_diagram_png(game: &ChessGame, options: &DiagramOptions, resources: &Path) -> Result<Vec<u8>, String> {
+	let diagram = try!(render_diagram(game, options, resources));
+	let (width, height) = diagram.dimensions();
+
+	let mut bytes = Vec::new();
+	match PNGEncoder::new(&mut bytes).encode(&diagram.into_raw(), width, height, ColorType::RGBA(8)) {
+		Ok(_) => Ok(bytes),
+		Err(e) => Err(format!("Could not encode diagram: {}", e))
+	}
+}
+
+pub fn save_diagram_png(game: &ChessGame, options: &DiagramOptions, resources: &Path, path: &Path) -> Result<(), String> {
+	let diagram = try!(render_diagram(game, options, resources));
+	diagram.save(path).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
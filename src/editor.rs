@@ -0,0 +1,366 @@
+use board::*;
+use clipboard::ClipboardContext;
+use error::ChessError;
+use fairy::PieceDefinition;
+use fen::{STARTING_FEN, placement_to_fen};
+use game::ChessGame;
+use panel::{PANEL_MARGIN, PANEL_WIDTH};
+use piston_window::*;
+use std::collections::HashMap;
+use theme::Theme;
+use variant::Rules;
+use view::{Renderable, DrawBackend, BoardLayout, Font, ScaledBackend, BOARD_ORIGIN, SQUARE_SIZE, board_layout_size,
+	draw_board_squares, draw_board_coordinates, fit_square_size, highlight_square};
+
+const PALETTE_SCALE: f64 = 0.7;
+const BUTTON_HEIGHT: f64 = 32.0;
+const BUTTON_SPACING: f64 = 8.0;
+const TEXT_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+const MESSAGE_COLOR: [f32; 4] = [0.7, 0.1, 0.1, 1.0];
+const BUTTON_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const ACTIVE_BUTTON_COLOR: [f32; 4] = [0.75, 0.87, 0.7, 1.0];
+
+// Castling rights in the order of their FEN letters
+const CASTLING_LETTERS: [char; 4] = ['K', 'Q', 'k', 'q'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditorButton {
+	SideToMove,
+	Castling(usize), // Index into CASTLING_LETTERS
+	Clear,
+	StartPosition,
+	CopyFen,
+	PasteFen,
+	Play
+}
+
+// A piece being carried by the mouse, either taken off the board or picked from the palette
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+	piece: ChessPiece,
+	from: Option<BoardCoordinates>
+}
+
+// Sets up a position to play from. Pieces are dragged onto the board from a palette beside it or
+// moved around on it, and right clicking a square empties it. Dragging a piece off the board removes it.
+#[derive(Debug)]
+pub struct PositionEditor {
+	board: ChessBoard<ChessPiece>,
+	side_to_move: ChessPieceColor,
+	castling: [bool; 4],
+	chess960: bool, // Carried over from the game being edited to the one played from the position
+	bottom_color: ChessPieceColor, // Likewise, whose side is drawn at the bottom
+	pieces: Vec<PieceDefinition>, // Likewise, the fairy pieces the position may use
+	rules: Rules, // Likewise, the variant played
+	pockets: HashMap<ChessPieceColor, Vec<ChessPieceType>>, // Likewise, what each side holds to drop
+	remaining_checks: HashMap<ChessPieceColor, u8>, // Likewise, the checks each side still has to give
+	scale: f64, // Window pixels per pixel of the layout, less than 1.0 when it is shrunk to fit the window
+	cursor: (f64, f64), // In the layout
+	drag: Option<Drag>,
+	message: Option<String> // Why the position can not be played, or what was last copied
+}
+
+impl PositionEditor {
+	// An editor starting from the current position of `game`
+	pub fn from_game(game: &ChessGame) -> PositionEditor {
+		let mut editor = PositionEditor {
+			board: ChessBoard::new(game.get_board().get_width(), game.get_board().get_height()),
+			side_to_move: ChessPieceColor::White,
+			castling: [false; 4],
+			chess960: game.is_chess960(),
+			bottom_color: game.get_bottom_player_color(),
+			pieces: game.get_piece_definitions().clone(),
+			rules: game.get_rules().clone(),
+			pockets: HashMap::new(),
+			remaining_checks: HashMap::new(),
+			scale: 1.0,
+			cursor: (0.0, 0.0),
+			drag: None,
+			message: None
+		};
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			editor.pockets.insert(color, game.get_pocket(color).clone());
+			editor.remaining_checks.insert(color, game.get_remaining_checks(color));
+		}
+		if let Err(e) = editor.load_fen(&game.to_fen()) {
+			editor.message = Some(e.to_string());
+		}
+		editor
+	}
+
+	// Sizes the squares so the board and panel fit a window of `window_size`
+	pub fn fit_to_window(&mut self, window_size: (f64, f64)) {
+		let layout_size = board_layout_size(self.board.get_dimensions(), PANEL_MARGIN * 2.0 + PANEL_WIDTH);
+		self.scale = fit_square_size(layout_size, window_size) / SQUARE_SIZE;
+	}
+
+	// Handles an event, returning the game to play once the position has been accepted
+	pub fn event(&mut self, e: &PistonWindow) -> Option<ChessGame> {
+		if let Some(position) = e.mouse_cursor_args() {
+			self.cursor = (position[0] / self.scale, position[1] / self.scale);
+		}
+
+		match e.press_args() {
+			Some(Button::Mouse(MouseButton::Left)) => {
+				return self.on_left_press();
+			},
+			Some(Button::Mouse(MouseButton::Right)) => {
+				self.drag = None;
+				if let Some(coordinates) = self.get_layout().square_at_position(self.cursor) {
+					let _ = self.board.set_contents_at_coordinates(&coordinates, None);
+				}
+			},
+			Some(Button::Keyboard(Key::E)) => {
+				return self.play();
+			},
+			_ => {}
+		}
+
+		if let Some(Button::Mouse(MouseButton::Left)) = e.release_args() {
+			self.on_left_release();
+		}
+
+		None
+	}
+
+	fn on_left_press(&mut self) -> Option<ChessGame> {
+		if let Some(button) = self.button_at_position(self.cursor) {
+			return self.press_button(button);
+		}
+
+		if let Some(piece) = self.palette_piece_at_position(self.cursor) {
+			self.drag = Some(Drag { piece: piece, from: None });
+			return None;
+		}
+
+		if let Some(coordinates) = self.get_layout().square_at_position(self.cursor) {
+			if let Ok(&Some(piece)) = self.board.get_contents_at_coordinates(&coordinates) {
+				self.drag = Some(Drag { piece: piece, from: Some(coordinates) });
+			}
+		}
+
+		None
+	}
+
+	fn on_left_release(&mut self) {
+		let drag = match self.drag.take() {
+			Some(drag) => drag,
+			None => { return; }
+		};
+
+		if let Some(from) = drag.from {
+			let _ = self.board.set_contents_at_coordinates(&from, None);
+		}
+		if let Some(coordinates) = self.get_layout().square_at_position(self.cursor) {
+			let _ = self.board.set_contents_at_coordinates(&coordinates, Some(drag.piece));
+		}
+	}
+
+	fn press_button(&mut self, button: EditorButton) -> Option<ChessGame> {
+		self.message = None;
+		match button {
+			EditorButton::SideToMove => { self.side_to_move = self.side_to_move.opposite_color(); },
+			EditorButton::Castling(i) => { self.castling[i] = !self.castling[i]; },
+			EditorButton::Clear => {
+				self.board = ChessBoard::new(self.board.get_width(), self.board.get_height());
+				self.castling = [false; 4];
+			},
+			EditorButton::StartPosition => {
+				let _ = self.load_fen(STARTING_FEN);
+			},
+			EditorButton::CopyFen => {
+				let fen = self.to_fen();
+				self.message = match ClipboardContext::new().and_then(|mut clipboard| clipboard.set_contents(fen.clone())) {
+					Ok(_) => match self.to_game() {
+						Ok(_) => Some(format!("Copied {}", fen)),
+						Err(e) => Some(format!("Copied, but {}", e))
+					},
+					Err(e) => Some(format!("Could not copy: {}", e))
+				};
+			},
+			EditorButton::PasteFen => {
+				let result = match ClipboardContext::new().and_then(|clipboard| clipboard.get_contents()) {
+					Ok(text) => self.load_fen(text.trim()).map_err(|e| e.to_string()),
+					Err(e) => Err(format!("Could not paste: {}", e))
+				};
+				if let Err(e) = result {
+					self.message = Some(e);
+				}
+			},
+			EditorButton::Play => {
+				return self.play();
+			}
+		}
+
+		None
+	}
+
+	// The game to play from the position, or None with the reason left in the message
+	fn play(&mut self) -> Option<ChessGame> {
+		match self.to_game() {
+			Ok(game) => Some(game),
+			Err(e) => {
+				self.message = Some(e.to_string());
+				None
+			}
+		}
+	}
+
+	pub fn to_fen(&self) -> String {
+		let side_to_move = match self.side_to_move {
+			ChessPieceColor::White => "w",
+			ChessPieceColor::Black => "b"
+		};
+
+		let mut castling: String = CASTLING_LETTERS.iter().zip(self.castling.iter())
+			.filter(|&(_, &enabled)| enabled)
+			.map(|(&letter, _)| letter)
+			.collect();
+		if castling.is_empty() {
+			castling.push('-');
+		}
+
+		format!("{} {} {} - 0 1", placement_to_fen(&self.board), side_to_move, castling)
+	}
+
+	pub fn load_fen(&mut self, fen: &str) -> Result<(), ChessError> {
+		let game = try!(ChessGame::from_fen_with_pieces(fen, self.pieces.clone()));
+		let castling = fen.split_whitespace().nth(2).unwrap_or("-");
+
+		self.board = game.get_board().clone();
+		self.side_to_move = game.get_side_to_move();
+		for (i, letter) in CASTLING_LETTERS.iter().enumerate() {
+			self.castling[i] = castling.contains(*letter);
+		}
+		Ok(())
+	}
+
+	// The position as a game by the rules of the one being edited, as long as it is one that could
+	// be played from
+	pub fn to_game(&self) -> Result<ChessGame, ChessError> {
+		let mut game = try!(ChessGame::from_fen_with_pieces(&self.to_fen(), self.pieces.clone()));
+		for (&color, pieces) in &self.pockets {
+			game.set_pocket(color, pieces.clone());
+		}
+		for (&color, &remaining) in &self.remaining_checks {
+			game.set_remaining_checks(color, remaining);
+		}
+		game.set_rules(self.rules.clone());
+		try!(game.validate_position());
+		if self.chess960 {
+			game.set_chess960(true);
+		}
+		game.set_bottom_player_color(self.bottom_color);
+		Ok(game)
+	}
+
+	fn get_layout(&self) -> BoardLayout {
+		BoardLayout::window(self.board.get_dimensions(), self.bottom_color == ChessPieceColor::Black)
+	}
+
+	fn get_panel_origin(&self) -> (f64, f64) {
+		(BOARD_ORIGIN.0 + self.board.get_width() as f64 * SQUARE_SIZE + PANEL_MARGIN, BOARD_ORIGIN.1)
+	}
+
+	// Window position of each palette piece, white's pieces above black's
+	fn palette_positions(&self) -> Vec<(ChessPiece, (f64, f64))> {
+		let (x, y) = self.get_panel_origin();
+		let size = SQUARE_SIZE * PALETTE_SCALE;
+
+		let mut positions = Vec::new();
+		for (row, &color) in [ChessPieceColor::White, ChessPieceColor::Black].iter().enumerate() {
+			for (col, &type_name) in PIECE_TYPES.iter().enumerate() {
+				positions.push((ChessPiece::new(type_name, color), (x + col as f64 * size, y + row as f64 * size)));
+			}
+		}
+		positions
+	}
+
+	fn palette_piece_at_position(&self, position: (f64, f64)) -> Option<ChessPiece> {
+		let size = SQUARE_SIZE * PALETTE_SCALE;
+		self.palette_positions().into_iter()
+			.find(|&(_, (x, y))| position.0 >= x && position.0 < x + size && position.1 >= y && position.1 < y + size)
+			.map(|(piece, _)| piece)
+	}
+
+	// Each button with its [x, y, width, height] rect and label, the castling toggles sharing a row
+	fn buttons(&self) -> Vec<(EditorButton, [f64; 4], String)> {
+		let (x, top) = self.get_panel_origin();
+		let mut y = top + SQUARE_SIZE * PALETTE_SCALE * 2.0 + 20.0;
+		let mut buttons = Vec::new();
+
+		buttons.push((EditorButton::SideToMove, [x, y, PANEL_WIDTH, BUTTON_HEIGHT], format!("{:?} to move", self.side_to_move)));
+		y += BUTTON_HEIGHT + BUTTON_SPACING;
+
+		let width = (PANEL_WIDTH - BUTTON_SPACING * 3.0) / 4.0;
+		for (i, letter) in CASTLING_LETTERS.iter().enumerate() {
+			let button_x = x + i as f64 * (width + BUTTON_SPACING);
+			buttons.push((EditorButton::Castling(i), [button_x, y, width, BUTTON_HEIGHT], format!("O-O {}", letter)));
+		}
+		y += BUTTON_HEIGHT + BUTTON_SPACING;
+
+		for &(button, label) in &[
+			(EditorButton::Clear, "Clear board"),
+			(EditorButton::StartPosition, "Starting position"),
+			(EditorButton::CopyFen, "Copy FEN"),
+			(EditorButton::PasteFen, "Paste FEN"),
+			(EditorButton::Play, "Play from here (E)")
+		] {
+			buttons.push((button, [x, y, PANEL_WIDTH, BUTTON_HEIGHT], label.to_string()));
+			y += BUTTON_HEIGHT + BUTTON_SPACING;
+		}
+
+		buttons
+	}
+
+	fn button_at_position(&self, position: (f64, f64)) -> Option<EditorButton> {
+		self.buttons().into_iter()
+			.find(|&(_, rect, _)| {
+				position.0 >= rect[0] && position.0 < rect[0] + rect[2] &&
+					position.1 >= rect[1] && position.1 < rect[1] + rect[3]
+			})
+			.map(|(button, _, _)| button)
+	}
+}
+
+impl Renderable for PositionEditor {
+	fn draw(&self, backend: &mut DrawBackend, theme: &Theme) {
+		let mut backend = ScaledBackend::new(backend, self.scale);
+		let backend = &mut backend;
+		let layout = self.get_layout();
+		draw_board_squares(backend, theme, &layout);
+		draw_board_coordinates(backend, theme, &layout, theme.coordinate_font_size);
+
+		let dragged_from = self.drag.and_then(|drag| drag.from);
+		if let Some(coordinates) = dragged_from {
+			highlight_square(backend, &layout, &coordinates, theme.selected_highlight);
+		}
+		self.board.draw_pieces(backend, &layout, dragged_from.as_ref());
+
+		for (piece, position) in self.palette_positions() {
+			backend.draw_sprite(&piece, position, PALETTE_SCALE);
+		}
+
+		let mut bottom = 0.0;
+		for (button, rect, label) in self.buttons() {
+			let active = match button {
+				EditorButton::Castling(i) => self.castling[i],
+				_ => false
+			};
+			backend.draw_rect(rect, if active { ACTIVE_BUTTON_COLOR } else { BUTTON_COLOR });
+			backend.draw_text(&label, Font::Interface, 16, TEXT_COLOR, (rect[0] + 10.0, rect[1] + 22.0));
+			bottom = rect[1] + rect[3];
+		}
+
+		if let Some(ref message) = self.message {
+			let (x, _) = self.get_panel_origin();
+			backend.draw_text(message, Font::Interface, 14, MESSAGE_COLOR, (x, bottom + 24.0));
+		}
+
+		// The carried piece follows the cursor, centred on it
+		if let Some(drag) = self.drag {
+			let position = (self.cursor.0 - SQUARE_SIZE / 2.0, self.cursor.1 - SQUARE_SIZE / 2.0);
+			backend.draw_sprite(&drag.piece, position, 1.0);
+		}
+	}
+}
@@ -0,0 +1,65 @@
+use board::BoardCoordinates;
+use std::error::Error;
+use std::fmt;
+use validate::PositionProblem;
+
+// Everything that can go wrong when working with a board or game, so callers can recover from bad
+// input instead of the library panicking
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChessError {
+	InvalidSquareName(String), // Not the name of a square, e.g. 'z9' or 'e'
+	OutOfBounds(BoardCoordinates), // Coordinates past the edge of the board
+	EmptySquare(BoardCoordinates), // No piece to move where one was expected
+	IllegalMove(String), // A move the rules do not allow in the current position
+	GameOver, // The game already has a result, so no more moves can be played
+	InvalidFen(String),
+	InvalidPgn(String),
+	InvalidData(String), // Saved or received data that could not be read back
+	UnsupportedVersion(u32), // Saved data written by a schema other than the one this build understands
+	Io(String), // Reading or writing a file failed
+	IllegalPosition(Vec<PositionProblem>), // A position that could not come about in a game
+	InvalidPiece(String) // A fairy piece definition that could not be read, e.g. bad Betza notation
+}
+
+impl fmt::Display for ChessError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			&ChessError::InvalidSquareName(ref name) => write!(f, "'{}' is not a square name", name),
+			&ChessError::OutOfBounds(coordinates) => {
+				write!(f, "Column {}, row {} is outside the board", coordinates.col, coordinates.row)
+			},
+			&ChessError::EmptySquare(coordinates) => write!(f, "There is no piece on {}", coordinates.to_string()),
+			&ChessError::IllegalMove(ref chess_move) => write!(f, "'{}' is not a legal move", chess_move),
+			&ChessError::GameOver => write!(f, "The game is over"),
+			&ChessError::InvalidFen(ref reason) => write!(f, "Invalid FEN: {}", reason),
+			&ChessError::InvalidPgn(ref reason) => write!(f, "Invalid PGN: {}", reason),
+			&ChessError::InvalidData(ref reason) => write!(f, "Invalid data: {}", reason),
+			&ChessError::UnsupportedVersion(version) => write!(f, "Unsupported schema version {}", version),
+			&ChessError::Io(ref reason) => write!(f, "{}", reason),
+			&ChessError::IllegalPosition(ref problems) => {
+				let problems: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+				write!(f, "Illegal position: {}", problems.join("; "))
+			},
+			&ChessError::InvalidPiece(ref reason) => write!(f, "Invalid piece: {}", reason)
+		}
+	}
+}
+
+impl Error for ChessError {
+	fn description(&self) -> &str {
+		match self {
+			&ChessError::InvalidSquareName(..) => "invalid square name",
+			&ChessError::OutOfBounds(..) => "coordinates out of bounds",
+			&ChessError::EmptySquare(..) => "empty square",
+			&ChessError::IllegalMove(..) => "illegal move",
+			&ChessError::GameOver => "game over",
+			&ChessError::InvalidFen(..) => "invalid FEN",
+			&ChessError::InvalidPgn(..) => "invalid PGN",
+			&ChessError::InvalidData(..) => "invalid data",
+			&ChessError::UnsupportedVersion(..) => "unsupported schema version",
+			&ChessError::Io(..) => "input/output error",
+			&ChessError::IllegalPosition(..) => "illegal position",
+			&ChessError::InvalidPiece(..) => "invalid piece"
+		}
+	}
+}
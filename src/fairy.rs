@@ -0,0 +1,421 @@
+use board::*;
+use error::ChessError;
+use game::ChessGame;
+#[cfg(feature = "serialization")]
+use serialization::SavedPiece;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+
+// Pieces whose letters are taken by the orthodox pieces
+const ORTHODOX_LETTERS: &'static str = "PNBRQK";
+
+// Size of the empty board a piece's mobility is measured on to estimate its value
+const VALUE_BOARD_SIZE: u8 = 8;
+
+// How a piece gets past other pieces on its line, Betza's 'p' and 'g' modifiers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hop {
+	Cannon, // Jumps exactly one piece anywhere on the line and lands on any square beyond it
+	Grasshopper // Jumps the first piece on the line and lands just beyond it
+}
+
+// One direction a piece moves in. Steps are seen from the owner's side of the board, so a positive
+// vertical step is forward for either color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Movement {
+	pub step: (i8, i8), // (horzontal, vertical)
+	pub range: u8, // Steps the piece may take along the line, 1 for a leaper and 0 for no limit
+	pub can_move: bool, // Onto an empty square
+	pub can_capture: bool,
+	pub hop: Option<Hop>
+}
+
+// A piece defined at runtime by its Betza notation, e.g. 'BN' for the Archbishop. It appears on the
+// board as ChessPieceType::Fairy with its letter.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialization", serde(into = "SavedPiece", try_from = "SavedPiece"))]
+pub struct PieceDefinition {
+	name: String,
+	letter: char, // Uppercase, as white's piece is written in FEN
+	betza: String,
+	value: u32, // In pawns, like ChessPieceType::get_value
+	movements: Vec<Movement>
+}
+
+impl PieceDefinition {
+	// A piece named `name`, written with `letter` and moving as `betza` describes. Its value is
+	// estimated from how many squares it reaches, `with_value` sets it where the estimate is off.
+	//
+	// The atoms are W, F, D, N, A, H, C (camel), Z (zebra) and G, with K, R, B and Q as shorthands.
+	// A doubled atom rides (WW is a rook) and a number after it limits the ride ('W3'). The prefixes
+	// m (move only), c (capture only), f, b, l, r, v and s (directions, each adding the moves going
+	// that way), p (cannon hop) and g (grasshopper hop) apply to the atom they come before. f or b
+	// followed by l, r or s is one direction between the two, so flF is only the forward left
+	// diagonal, and a doubled f, b, l or r is the narrow form of it, so ffN is the two most forward
+	// knight moves.
+	pub fn new(name: &str, letter: char, betza: &str) -> Result<PieceDefinition, ChessError> {
+		let invalid = |reason: String| ChessError::InvalidPiece(format!("{}: {}", name, reason));
+
+		if !letter.is_ascii() || !letter.is_alphabetic() {
+			return Err(invalid(format!("'{}' is not a letter", letter)));
+		}
+		let letter = letter.to_ascii_uppercase();
+		if ORTHODOX_LETTERS.contains(letter) {
+			return Err(invalid(format!("'{}' is the letter of an orthodox piece", letter)));
+		}
+
+		let movements = try!(parse_betza(betza).map_err(invalid));
+		if movements.is_empty() {
+			return Err(invalid(format!("'{}' has no moves", betza)));
+		}
+
+		let mut definition = PieceDefinition {
+			name: name.to_string(),
+			letter: letter,
+			betza: betza.to_string(),
+			value: 0,
+			movements: movements
+		};
+		definition.value = definition.estimate_value();
+		Ok(definition)
+	}
+
+	pub fn with_value(mut self, value: u32) -> PieceDefinition {
+		self.value = value;
+		self
+	}
+
+	pub fn get_name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn get_letter(&self) -> char {
+		self.letter
+	}
+
+	pub fn get_betza(&self) -> &str {
+		&self.betza
+	}
+
+	pub fn get_value(&self) -> u32 {
+		self.value
+	}
+
+	pub fn get_movements(&self) -> &Vec<Movement> {
+		&self.movements
+	}
+
+	pub fn get_type(&self) -> ChessPieceType {
+		ChessPieceType::Fairy(self.letter)
+	}
+
+	// Roughly 0.4 pawns for every square reached on average from the squares of an empty board,
+	// which puts the knight at 2, the rook at 6 and the queen at 9
+	fn estimate_value(&self) -> u32 {
+		let board: ChessBoard<ChessPiece> = ChessBoard::new(VALUE_BOARD_SIZE, VALUE_BOARD_SIZE);
+		let squares = board.get_all_coordinates();
+		let reached: usize = squares.iter()
+			.map(|start| {
+				self.movements.iter()
+					.map(|movement| movement_targets(&board, start, ChessPieceColor::White, 1, movement, false).len())
+					.sum::<usize>()
+			})
+			.sum();
+
+		let mobility = reached as f64 / squares.len() as f64;
+		((mobility * 0.4).round() as u32).max(1)
+	}
+}
+
+#[cfg(feature = "serialization")]
+impl From<PieceDefinition> for SavedPiece {
+	fn from(definition: PieceDefinition) -> SavedPiece {
+		SavedPiece {
+			name: definition.name,
+			letter: definition.letter,
+			betza: definition.betza,
+			value: definition.value
+		}
+	}
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<SavedPiece> for PieceDefinition {
+	type Error = ChessError;
+
+	fn try_from(saved: SavedPiece) -> Result<PieceDefinition, ChessError> {
+		PieceDefinition::new(&saved.name, saved.letter, &saved.betza).map(|definition| definition.with_value(saved.value))
+	}
+}
+
+// Every movement described by Betza notation, see `PieceDefinition::new`
+fn parse_betza(betza: &str) -> Result<Vec<Movement>, String> {
+	let chars: Vec<char> = betza.chars().collect();
+	let mut movements: Vec<Movement> = Vec::new();
+	let mut i = 0;
+
+	while i < chars.len() {
+		let mut modifiers = String::new();
+		while i < chars.len() && chars[i].is_lowercase() {
+			modifiers.push(chars[i]);
+			i += 1;
+		}
+		let atom = match chars.get(i) {
+			Some(&atom) => atom,
+			None => { return Err(format!("'{}' ends without an atom", betza)); }
+		};
+		i += 1;
+
+		// The shorthands are leapers or riders of the basic atoms
+		let (atoms, mut range) = match atom {
+			'K' => (vec![(0, 1), (1, 1)], 1),
+			'R' => (vec![(0, 1)], 0),
+			'B' => (vec![(1, 1)], 0),
+			'Q' => (vec![(0, 1), (1, 1)], 0),
+			_ => match atom_offset(atom) {
+				Some(offset) => (vec![offset], 1),
+				None => { return Err(format!("'{}' is not a Betza atom", atom)); }
+			}
+		};
+
+		if chars.get(i) == Some(&atom) {
+			range = 0;
+			i += 1;
+		} else {
+			let digits: String = chars[i..].iter().take_while(|c| c.is_digit(10)).cloned().collect();
+			if !digits.is_empty() {
+				range = try!(digits.parse::<u8>().map_err(|_| format!("'{}' is not a range", digits)));
+				i += digits.len();
+			}
+		}
+
+		let mut can_move = true;
+		let mut can_capture = true;
+		let mut hop = None;
+		let mut directions = String::new();
+		for modifier in modifiers.chars() {
+			match modifier {
+				'm' => { can_capture = false; },
+				'c' => { can_move = false; },
+				'p' => { hop = Some(Hop::Cannon); },
+				'g' => { hop = Some(Hop::Grasshopper); },
+				'f' | 'b' | 'l' | 'r' | 'v' | 's' => { directions.push(modifier); },
+				_ => { return Err(format!("Unsupported modifier '{}'", modifier)); }
+			}
+		}
+		if !can_move && !can_capture {
+			return Err(format!("'{}{}' can neither move nor capture", modifiers, atom));
+		}
+		if hop.is_some() && range == 1 {
+			return Err(format!("'{}{}' hops but does not ride", modifiers, atom));
+		}
+
+		let direction_sets = get_direction_sets(&directions);
+		let steps: Vec<(i8, i8)> = atoms.iter()
+			.flat_map(|&(a, b)| symmetric_steps(a, b).into_iter())
+			.filter(|&step| {
+				direction_sets.is_empty() || direction_sets.iter().any(|set| set.iter().all(|&direction| is_towards(step, direction)))
+			})
+			.collect();
+		if steps.is_empty() {
+			return Err(format!("'{}{}' leaves the piece no direction to go", modifiers, atom));
+		}
+
+		for step in steps {
+			let movement = Movement {
+				step: step,
+				range: range,
+				can_move: can_move,
+				can_capture: can_capture,
+				hop: hop
+			};
+			if !movements.contains(&movement) {
+				movements.push(movement);
+			}
+		}
+	}
+
+	Ok(movements)
+}
+
+// The leap of a single Betza atom, the smaller distance first
+fn atom_offset(atom: char) -> Option<(i8, i8)> {
+	match atom {
+		'W' => Some((0, 1)),
+		'F' => Some((1, 1)),
+		'D' => Some((0, 2)),
+		'N' => Some((1, 2)),
+		'A' => Some((2, 2)),
+		'H' => Some((0, 3)),
+		'C' => Some((1, 3)),
+		'Z' => Some((2, 3)),
+		'G' => Some((3, 3)),
+		_ => None
+	}
+}
+
+// The up to eight ways a leap of `a` squares one way and `b` the other can be turned
+fn symmetric_steps(a: i8, b: i8) -> Vec<(i8, i8)> {
+	let mut steps = Vec::new();
+	for &(x, y) in &[(a, b), (b, a)] {
+		for &(sign_x, sign_y) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+			let step = (x * sign_x, y * sign_y);
+			if !steps.contains(&step) {
+				steps.push(step);
+			}
+		}
+	}
+	steps
+}
+
+// The direction letters grouped into the sets a step has to go all the ways of. A step has to match one
+// of the sets. f or b with l, r or s after it is a single set, as is a doubled f, b, l or r, which is
+// its narrow form: mostly vertical for f and b, mostly sideways for l and r.
+fn get_direction_sets(directions: &str) -> Vec<Vec<char>> {
+	let letters: Vec<char> = directions.chars().collect();
+	let mut sets = Vec::new();
+	let mut i = 0;
+
+	while i < letters.len() {
+		let letter = letters[i];
+		let set = match (letter, letters.get(i + 1).cloned()) {
+			('f', Some(side)) | ('b', Some(side)) if side == 'l' || side == 'r' || side == 's' => vec![letter, side],
+			('f', Some(next)) | ('b', Some(next)) if next == letter => vec![letter, 'v'],
+			('l', Some(next)) | ('r', Some(next)) if next == letter => vec![letter, 's'],
+			_ => vec![letter]
+		};
+		i += if set.len() == 2 { 2 } else { 1 };
+		sets.push(set);
+	}
+
+	sets
+}
+
+// Whether a step goes the way of a Betza direction letter: forwards, backwards, left, right,
+// mostly vertically or mostly sideways
+fn is_towards(step: (i8, i8), direction: char) -> bool {
+	let (x, y) = step;
+	match direction {
+		'f' => y > 0,
+		'b' => y < 0,
+		'l' => x < 0,
+		'r' => x > 0,
+		'v' => y.abs() > x.abs(),
+		's' => x.abs() > y.abs(),
+		_ => false
+	}
+}
+
+// The squares `movement` reaches from `start` for a piece of `color`, each with whether it captures
+// there. `forward` is the color's direction along the rows. With `attack` set every square the piece
+// could capture on is listed whatever stands there, which is what matters for whether it is attacked.
+fn movement_targets(
+	board: &ChessBoard<ChessPiece>,
+	start: &BoardCoordinates,
+	color: ChessPieceColor,
+	forward: i8,
+	movement: &Movement,
+	attack: bool
+) -> Vec<(BoardCoordinates, bool)> {
+	let mut targets = Vec::new();
+	let (horzontal, vertical) = (movement.step.0 * forward, movement.step.1 * forward);
+	let mut position = *start;
+	let mut distance = 0u32;
+	let mut hopped = false;
+
+	loop {
+		distance += 1;
+		if movement.hop.is_none() && movement.range != 0 && distance > movement.range as u32 {
+			break;
+		}
+
+		let cell = match board.get_move_destination(&position, horzontal, vertical) {
+			Some(cell) => cell,
+			None => { break; }
+		};
+		position = *cell.get_coordinates();
+
+		// Hoppers pass over the first piece on their line without landing before it
+		if movement.hop.is_some() && !hopped {
+			hopped = cell.get_contents().is_some();
+			continue;
+		}
+
+		match cell.get_contents() {
+			&Some(piece) => {
+				if movement.can_capture && (attack || piece.get_color() != color) {
+					targets.push((position, true));
+				}
+				break;
+			},
+			&None => {
+				if attack && movement.can_capture {
+					targets.push((position, true));
+				} else if !attack && movement.can_move {
+					targets.push((position, false));
+				}
+			}
+		}
+
+		if movement.hop == Some(Hop::Grasshopper) {
+			break;
+		}
+	}
+
+	targets
+}
+
+impl ChessGame {
+	// Moves of a fairy piece by its definition, ignoring checks. Pieces without a definition in this
+	// game do not move.
+	pub fn get_fairy_moves(&self, piece: &ChessPiece, start: &BoardCoordinates) -> Vec<ChessMove> {
+		let definition = match piece.get_type() {
+			ChessPieceType::Fairy(letter) => match self.get_piece_definition(letter) {
+				Some(definition) => definition,
+				None => { return Vec::new(); }
+			},
+			_ => { return Vec::new(); }
+		};
+
+		let forward = self.get_pawn_direction(piece.get_color());
+		let mut result: Vec<ChessMove> = Vec::new();
+		for movement in definition.get_movements() {
+			for (end, _) in movement_targets(self.get_board(), start, piece.get_color(), forward, movement, false) {
+				let chess_move = ChessMove::new(start, &end);
+				if !result.contains(&chess_move) {
+					result.push(chess_move);
+				}
+			}
+		}
+
+		result
+	}
+
+	// Whether a fairy piece of `by_color` could capture on `target`
+	pub fn is_attacked_by_fairy_piece(&self, board: &ChessBoard<ChessPiece>, target: &BoardCoordinates, by_color: ChessPieceColor) -> bool {
+		if self.get_piece_definitions().is_empty() {
+			return false;
+		}
+
+		let forward = self.get_pawn_direction(by_color);
+		board.get_all_coordinates().iter().any(|coordinates| {
+			let definition = match board.get_contents_at_coordinates(coordinates) {
+				Ok(&Some(piece)) if piece.get_color() == by_color => match piece.get_type() {
+					ChessPieceType::Fairy(letter) => self.get_piece_definition(letter),
+					_ => None
+				},
+				_ => None
+			};
+
+			definition.map_or(false, |definition| {
+				definition.get_movements().iter()
+					.filter(|movement| movement.can_capture)
+					.any(|movement| {
+						movement_targets(board, coordinates, by_color, forward, movement, true).iter()
+							.any(|&(square, _)| square == *target)
+					})
+			})
+		})
+	}
+}
@@ -0,0 +1,370 @@
+use board::*;
+use error::ChessError;
+use fairy::PieceDefinition;
+use game::{CastlingRight, ChessGame, CHECKS_TO_WIN};
+use variant::WinCondition;
+
+pub const STARTING_FEN: &'static str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// Piece for a FEN letter, uppercase for white and lowercase for black
+pub fn piece_from_letter(letter: char) -> Option<ChessPiece> {
+	let color = if letter.is_uppercase() { ChessPieceColor::White } else { ChessPieceColor::Black };
+	let type_name = match letter.to_lowercase().next() {
+		Some('p') => ChessPieceType::Pawn,
+		Some('r') => ChessPieceType::Rook,
+		Some('n') => ChessPieceType::Knight,
+		Some('b') => ChessPieceType::Bishop,
+		Some('q') => ChessPieceType::Queen,
+		Some('k') => ChessPieceType::King,
+		_ => { return None; }
+	};
+
+	Some(ChessPiece::new(type_name, color))
+}
+
+impl ChessGame {
+	// A game set up from Forsyth-Edwards Notation, with white moving up the board. The move
+	// counters may be left off, as many tools do. Castling rights may name the rook's file instead
+	// of its side, as in Shredder-FEN and X-FEN, and games where castling can not be written with
+	// KQkq or starts from anything but the usual squares are Chess960 games. A Three-check game has
+	// the checks each side still has to give after the en passant square, e.g. '3+2'.
+	pub fn from_fen(fen: &str) -> Result<ChessGame, ChessError> {
+		ChessGame::from_fen_with_pieces(fen, Vec::new())
+	}
+
+	// A game set up from a FEN that may use the letters of the given fairy pieces
+	pub fn from_fen_with_pieces(fen: &str, pieces: Vec<PieceDefinition>) -> Result<ChessGame, ChessError> {
+		ChessGame::parse_fen(fen, pieces).map_err(ChessError::InvalidFen)
+	}
+
+	fn parse_fen(fen: &str, pieces: Vec<PieceDefinition>) -> Result<ChessGame, String> {
+		let mut fields: Vec<&str> = fen.split_whitespace().collect();
+		let checks = match fields.get(4) {
+			Some(field) if field.contains('+') => Some(*field),
+			_ => None
+		};
+		if checks.is_some() {
+			fields.remove(4);
+		}
+		if fields.len() < 4 || fields.len() > 6 {
+			return Err(format!("FEN needs 4 to 6 fields, found {}", fields.len()));
+		}
+
+		// Crazyhouse pockets follow the placement in brackets, e.g. '[QNpp]'
+		let (placement, pockets) = match fields[0].find('[') {
+			Some(index) if fields[0].ends_with(']') => (&fields[0][..index], Some(&fields[0][index + 1..fields[0].len() - 1])),
+			Some(..) => { return Err(format!("Unclosed pocket in '{}'", fields[0])); },
+			None => (fields[0], None)
+		};
+		let (board, promoted) = try!(parse_placement(placement, &pieces));
+
+		let side_to_move = match fields[1] {
+			"w" => ChessPieceColor::White,
+			"b" => ChessPieceColor::Black,
+			other => { return Err(format!("Invalid side to move '{}'", other)); }
+		};
+
+		let mut game = ChessGame::from_board(board, side_to_move);
+		for definition in pieces {
+			try!(game.add_piece_definition(definition).map_err(|e| e.to_string()));
+		}
+		// Drops are turned on once the pockets are filled, so the result takes them into account
+		if let Some(pockets) = pockets {
+			for letter in pockets.chars().filter(|&letter| letter != '-') {
+				let piece = try!(game.piece_from_letter(letter).ok_or(format!("Invalid piece '{}' in pocket", letter)));
+				if piece.get_type() == ChessPieceType::King {
+					return Err("A king can not be in a pocket".to_string());
+				}
+				game.add_to_pocket(piece.get_color(), piece.get_type());
+			}
+			game.set_promoted(promoted);
+			let mut rules = game.get_rules().clone();
+			rules.drops = true;
+			game.set_rules(rules);
+		}
+
+		let rights = try!(parse_castling(&game, fields[2]));
+		let chess960 = fields[2].chars().any(|letter| !"KQkq-".contains(letter)) ||
+			rights.iter().any(|right| !game.is_standard_castling_right(right));
+		game.set_castling_rights(rights);
+		game.set_chess960(chess960);
+
+		let en_passant = match fields[3] {
+			"-" => None,
+			square => Some(try!(BoardCoordinates::new(square).map_err(|e| e.to_string())))
+		};
+		game.set_en_passant(en_passant);
+
+		let halfmove_clock = match fields.get(4) {
+			Some(field) => try!(field.parse::<u32>().map_err(|_| format!("Invalid halfmove clock '{}'", field))),
+			None => 0
+		};
+		let fullmove_number = match fields.get(5) {
+			Some(field) => try!(field.parse::<u32>().map_err(|_| format!("Invalid fullmove number '{}'", field))),
+			None => 1
+		};
+		game.set_move_counters(halfmove_clock, fullmove_number);
+
+		// Three-check is turned on once the counts are known, like drops
+		if let Some(checks) = checks {
+			let mut counts = checks.split('+').map(|count| count.parse::<u8>());
+			match (counts.next(), counts.next(), counts.next()) {
+				(Some(Ok(white)), Some(Ok(black)), None) if white <= CHECKS_TO_WIN && black <= CHECKS_TO_WIN => {
+					game.set_remaining_checks(ChessPieceColor::White, white);
+					game.set_remaining_checks(ChessPieceColor::Black, black);
+				},
+				_ => { return Err(format!("Invalid remaining checks '{}'", checks)); }
+			}
+			let mut rules = game.get_rules().clone();
+			if !rules.win_conditions.contains(&WinCondition::ThreeCheck) {
+				rules.win_conditions.push(WinCondition::ThreeCheck);
+			}
+			game.set_rules(rules);
+		}
+
+		game.update_position_result();
+		Ok(game)
+	}
+
+	// The position in Forsyth-Edwards Notation, reading the bottom row as the first rank. Castling
+	// with a rook that is not the outermost one on its side of the king is written with the rook's
+	// file, as in X-FEN.
+	pub fn to_fen(&self) -> String {
+		self.to_fen_with_castling(false)
+	}
+
+	// The position in Shredder-FEN, which writes every castling right as the file of its rook
+	pub fn to_shredder_fen(&self) -> String {
+		self.to_fen_with_castling(true)
+	}
+
+	fn to_fen_with_castling(&self, shredder: bool) -> String {
+		let side_to_move = match self.get_side_to_move() {
+			ChessPieceColor::White => "w",
+			ChessPieceColor::Black => "b"
+		};
+
+		let en_passant = match self.get_en_passant() {
+			Some(square) => square.to_string(),
+			None => "-".to_string()
+		};
+
+		let checks = if self.get_rules().win_conditions.contains(&WinCondition::ThreeCheck) {
+			format!(" {}+{}", self.get_remaining_checks(ChessPieceColor::White), self.get_remaining_checks(ChessPieceColor::Black))
+		} else {
+			String::new()
+		};
+
+		format!(
+			"{} {} {} {}{} {} {}",
+			self.get_placement_string(), side_to_move, self.get_castling_string(shredder), en_passant, checks,
+			self.get_halfmove_clock(), self.get_fullmove_number()
+		)
+	}
+
+	// The piece placement, and with drops the promoted pieces marked with '~' and both pockets in
+	// brackets after it, white's first
+	fn get_placement_string(&self) -> String {
+		if !self.get_rules().drops {
+			return placement_to_fen(self.get_board());
+		}
+
+		let pockets: String = [ChessPieceColor::White, ChessPieceColor::Black].iter()
+			.flat_map(|&color| self.get_pocket(color).iter().map(move |&type_name| ChessPiece::new(type_name, color).to_letter()))
+			.collect();
+		format!("{}[{}]", write_placement(self.get_board(), |coordinates| self.is_promoted(coordinates)), pockets)
+	}
+
+	// The piece for a FEN letter, fairy pieces included
+	fn piece_from_letter(&self, letter: char) -> Option<ChessPiece> {
+		piece_from_letter(letter).or_else(|| {
+			let color = if letter.is_uppercase() { ChessPieceColor::White } else { ChessPieceColor::Black };
+			self.get_piece_definition(letter.to_ascii_uppercase())
+				.map(|definition| ChessPiece::new(definition.get_type(), color))
+		})
+	}
+
+	// Castling rights as KQkq, or rook files for `shredder` and rooks KQkq would not pick out, white's
+	// first and king side before queen side
+	fn get_castling_string(&self, shredder: bool) -> String {
+		let outermost = self.get_default_castling_rights();
+		let mut castling = String::new();
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			for &king_side in &[true, false] {
+				let rights = self.get_castling_rights().iter()
+					.filter(|right| right.color == color && self.is_king_side_rook(right) == king_side);
+				for right in rights {
+					let letter = if shredder || !outermost.contains(right) {
+						right.rook.to_string().chars().next().unwrap().to_ascii_uppercase()
+					} else if king_side {
+						'K'
+					} else {
+						'Q'
+					};
+					castling.push(if color == ChessPieceColor::White { letter } else { letter.to_ascii_lowercase() });
+				}
+			}
+		}
+
+		if castling.is_empty() {
+			castling.push('-');
+		}
+		castling
+	}
+
+	// Whether the rook of a castling right stands on the king's higher column side
+	fn is_king_side_rook(&self, right: &CastlingRight) -> bool {
+		let board = self.get_board();
+		let king = ChessPiece::new(ChessPieceType::King, right.color);
+		(0..board.get_width())
+			.find(|&col| board.get_contents_at_coordinates(&(col, right.rook.row).into()) == Ok(&Some(king)))
+			.map_or(right.rook.col > board.get_width() / 2, |king_col| right.rook.col > king_col)
+	}
+}
+
+// The piece placement field of a FEN, the bottom row of the board being the first rank. Boards of
+// other sizes have as many ranks as rows, with runs of empty squares past 9 written as e.g. '10'.
+pub fn placement_to_fen(board: &ChessBoard<ChessPiece>) -> String {
+	write_placement(board, |_| false)
+}
+
+// The placement with '~' after each piece `is_promoted` picks out
+fn write_placement<F: Fn(&BoardCoordinates) -> bool>(board: &ChessBoard<ChessPiece>, is_promoted: F) -> String {
+	let (width, height) = board.get_dimensions();
+
+	let mut ranks: Vec<String> = Vec::new();
+	for row in (0..height).rev() {
+		let mut rank = String::new();
+		let mut empty = 0;
+		for col in 0..width {
+			match board.get_contents_at_coordinates(&(col, row).into()) {
+				Ok(&Some(piece)) => {
+					if empty > 0 {
+						rank.push_str(&empty.to_string());
+						empty = 0;
+					}
+					rank.push(piece.to_letter());
+					if is_promoted(&(col, row).into()) {
+						rank.push('~');
+					}
+				},
+				_ => { empty += 1; }
+			}
+		}
+		if empty > 0 {
+			rank.push_str(&empty.to_string());
+		}
+		ranks.push(rank);
+	}
+
+	ranks.join("/")
+}
+
+// The board and the squares of the pieces marked as promoted with '~'
+fn parse_placement(placement: &str, pieces: &[PieceDefinition]) -> Result<(ChessBoard<ChessPiece>, Vec<BoardCoordinates>), String> {
+	let ranks: Vec<&str> = placement.split('/').collect();
+	if ranks.len() > 255 {
+		return Err(format!("Too many ranks, found {}", ranks.len()));
+	}
+
+	// Each rank as its squares, empty ones as None, and the rank and column of each promoted piece
+	let mut rows: Vec<Vec<Option<ChessPiece>>> = Vec::new();
+	let mut promoted: Vec<(usize, usize)> = Vec::new();
+	for (i, rank) in ranks.iter().enumerate() {
+		let rank_number = ranks.len() - i;
+		let mut squares = Vec::new();
+		let mut empty = 0usize;
+		for letter in rank.chars() {
+			if let Some(digit) = letter.to_digit(10) {
+				empty = empty * 10 + digit as usize;
+				if empty > 255 {
+					return Err(format!("Too many squares in rank {}", rank_number));
+				}
+				continue;
+			}
+			if letter == '~' {
+				match squares.last() {
+					Some(&Some(..)) if empty == 0 => { promoted.push((i, squares.len() - 1)); },
+					_ => { return Err(format!("'~' does not follow a piece in rank {}", rank_number)); }
+				}
+				continue;
+			}
+
+			squares.extend((0..empty).map(|_| None));
+			empty = 0;
+			let fairy_piece = || {
+				let color = if letter.is_uppercase() { ChessPieceColor::White } else { ChessPieceColor::Black };
+				pieces.iter()
+					.find(|definition| definition.get_letter() == letter.to_ascii_uppercase())
+					.map(|definition| ChessPiece::new(definition.get_type(), color))
+			};
+			match piece_from_letter(letter).or_else(fairy_piece) {
+				Some(piece) => { squares.push(Some(piece)); },
+				None => { return Err(format!("Invalid piece '{}' in rank {}", letter, rank_number)); }
+			}
+		}
+		squares.extend((0..empty).map(|_| None));
+		rows.push(squares);
+	}
+
+	// Every rank has to be as wide as the first
+	let width = rows[0].len();
+	if width == 0 || width > 255 {
+		return Err(format!("Rank {} has {} squares", ranks.len(), width));
+	}
+	for (i, squares) in rows.iter().enumerate() {
+		if squares.len() != width {
+			return Err(format!("Rank {} has {} squares instead of {}", ranks.len() - i, squares.len(), width));
+		}
+	}
+
+	let height = rows.len();
+	let mut board: ChessBoard<ChessPiece> = ChessBoard::new(width as u8, height as u8);
+	for (i, squares) in rows.into_iter().enumerate() {
+		let row = (height - 1 - i) as u8;
+		for (col, contents) in squares.into_iter().enumerate() {
+			try!(board.set_contents_at_coordinates(&(col as u8, row).into(), contents).map_err(|e| e.to_string()));
+		}
+	}
+
+	let promoted = promoted.into_iter()
+		.map(|(i, col)| BoardCoordinates { col: col as u8, row: (height - 1 - i) as u8 })
+		.collect();
+	Ok((board, promoted))
+}
+
+// Castling rights, each KQkq letter naming the outermost rook on that side of the king and each
+// file letter the rook on that file
+fn parse_castling(game: &ChessGame, castling: &str) -> Result<Vec<CastlingRight>, String> {
+	let mut rights = Vec::new();
+	if castling == "-" {
+		return Ok(rights);
+	}
+
+	let available = game.get_default_castling_rights();
+	for letter in castling.chars() {
+		let color = if letter.is_uppercase() { ChessPieceColor::White } else { ChessPieceColor::Black };
+		let right = match letter {
+			'K' | 'k' | 'Q' | 'q' => {
+				let king_side = letter == 'K' || letter == 'k';
+				available.iter().find(|right| right.color == color && game.is_king_side_rook(right) == king_side).cloned()
+			},
+			_ => match BoardCoordinates::char_to_column(letter.to_ascii_lowercase()) {
+				Some(col) => Some(CastlingRight { color: color, rook: (col, game.get_home_row(color)).into() }),
+				None => { return Err(format!("Invalid castling rights '{}'", castling)); }
+			}
+		};
+
+		match right {
+			Some(right) if game.is_castling_right_possible(&right) => {
+				if !rights.contains(&right) {
+					rights.push(right);
+				}
+			},
+			_ => { return Err(format!("Castling right '{}' has no king and rook to go with it", letter)); }
+		}
+	}
+
+	Ok(rights)
+}
+
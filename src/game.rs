@@ -1,52 +1,1044 @@
 use board::*;
+use clock::ChessClock;
+use error::ChessError;
+use fairy::PieceDefinition;
+#[cfg(feature = "serialization")]
+use serialization::{SavedGame, SCHEMA_VERSION};
+use std::cmp;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
 use std::collections::HashMap;
-use view::Renderable;
-use opengl_graphics::GlGraphics;
-use piston_window::*;
+use theme::Theme;
+use variant::{Rules, StalemateRule, WinCondition};
+use view::{Renderable, DrawBackend, BoardLayout, draw_board_squares, draw_board_coordinates};
 
-#[derive(Debug)]
+// Time each player starts with, in seconds
+const DEFAULT_CLOCK_SECONDS: f64 = 600.0;
+
+// Checks a player has to give to win Three-check
+pub const CHECKS_TO_WIN: u8 = 3;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialization", serde(into = "SavedGame", try_from = "SavedGame"))]
 pub struct ChessGame {
-	board: ChessBoard<ChessPiece>,
-	colors_directions: HashMap<ChessPieceColor, Direction>
+	board: ChessBoard<ChessPiece>, // Row 0 is always white's back rank and column 0 the a-file
+	bottom_color: ChessPieceColor, // Whose side is drawn at the bottom, rules never depend on it
+	side_to_move: ChessPieceColor,
+	history: Vec<MoveRecord>,
+	clocks: HashMap<ChessPieceColor, ChessClock>,
+	result: Option<GameResult>,
+	castling_rights: Vec<CastlingRight>,
+	en_passant: Option<BoardCoordinates>, // The square a pawn just skipped over with a double step
+	halfmove_clock: u32, // Moves since the last capture or pawn move
+	fullmove_number: u32,
+	chess960: bool, // Whether castling is written as the king taking its own rook
+	pieces: Vec<PieceDefinition>, // Fairy pieces that may appear on the board
+	rules: Rules,
+	pockets: HashMap<ChessPieceColor, Vec<ChessPieceType>>, // Pieces each side holds to drop, most valuable first
+	promoted: Vec<BoardCoordinates>, // Squares of pieces that were pawns, which are pocketed as pawns when taken
+	checks_given: HashMap<ChessPieceColor, u8> // Counted for Three-check
+}
+
+// A rook that has not moved, which the king of the same color may still castle with
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct CastlingRight {
+	pub color: ChessPieceColor,
+	pub rook: BoardCoordinates
 }
 
+// A move that has been played, along with what it did to the board
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct MoveRecord {
+	pub chess_move: ChessMove,
+	pub piece: ChessPiece,
+	pub captured: Option<ChessPiece>,
+	pub captured_as: Option<ChessPieceType>, // What the captured piece counts as in a pocket, a pawn if it was promoted
+	pub notation: String // Standard algebraic notation, e.g. 'Nxe5+'
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum GameResult {
+	Win(ChessPieceColor, WinReason),
+	Draw(DrawReason)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum WinReason {
+	Checkmate,
+	Stalemate,
+	Timeout,
+	OtherBoard, // The team won on the other board of a bughouse game
+	Explosion, // The king was blown up by a capture next to it, in atomic chess
+	KingOfTheHill, // The king reached one of the centre squares
+	ThreeChecks,
+	AllPiecesLost // The winner has no pieces left, in antichess
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum DrawReason {
+	Stalemate,
+	OtherBoard // The game on the other bughouse board was drawn
+}
+
+impl GameResult {
+	// The score as written at the end of a game record
+	pub fn get_score(&self) -> &'static str {
+		match self {
+			&GameResult::Win(ChessPieceColor::White, _) => "1-0",
+			&GameResult::Win(ChessPieceColor::Black, _) => "0-1",
+			&GameResult::Draw(_) => "1/2-1/2"
+		}
+	}
+}
+
+impl ToString for GameResult {
+	fn to_string(&self) -> String {
+		match self {
+			&GameResult::Win(winner, reason) => {
+				let reason = match reason {
+					WinReason::Checkmate => "checkmate",
+					WinReason::Stalemate => "stalemate",
+					WinReason::Timeout => "timeout",
+					WinReason::OtherBoard => "the result on the other board",
+					WinReason::Explosion => "exploding the king",
+					WinReason::KingOfTheHill => "reaching the centre",
+					WinReason::ThreeChecks => "giving three checks",
+					WinReason::AllPiecesLost => "losing all pieces"
+				};
+				format!("{:?} wins by {}", winner, reason)
+			},
+			&GameResult::Draw(DrawReason::Stalemate) => "Draw by stalemate".to_string(),
+			&GameResult::Draw(DrawReason::OtherBoard) => "Draw on the other board".to_string()
+		}
+	}
+}
+
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+	(-2, -1),
+	(-2, 1),
+	(2, -1),
+	(2, 1),
+	(-1, -2),
+	(-1, 2),
+	(1, -2),
+	(1, 2)
+];
+
+const ADJACENT_OFFSETS: [(i8, i8); 8] = [
+	(-1, -1),
+	(-1, 0),
+	(-1, 1),
+	(0, -1),
+	(0, 1),
+	(1, -1),
+	(1, 0),
+	(1, 1)
+];
+
 #[allow(dead_code)]
 impl ChessGame {
 
-	pub fn new(up_color: ChessPieceColor) -> ChessGame {
+	// An empty board, shown with `bottom_color`'s side at the bottom
+	pub fn new(bottom_color: ChessPieceColor) -> ChessGame {
 		let mut game = ChessGame {
-			board: ChessBoard::new(8),
-			colors_directions: HashMap::new()
+			board: ChessBoard::new(8, 8),
+			bottom_color: bottom_color,
+			side_to_move: ChessPieceColor::White,
+			history: Vec::new(),
+			clocks: HashMap::new(),
+			result: None,
+			castling_rights: Vec::new(),
+			en_passant: None,
+			halfmove_clock: 0,
+			fullmove_number: 1,
+			chess960: false,
+			pieces: Vec::new(),
+			rules: Rules::default(),
+			pockets: HashMap::new(),
+			promoted: Vec::new(),
+			checks_given: HashMap::new()
+		};
+
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			game.pockets.insert(color, Vec::new());
+			game.checks_given.insert(color, 0);
+		}
+		game.set_time_control(DEFAULT_CLOCK_SECONDS, 0.0);
+		game
+	}
+
+	// Sets up the standard starting position, keeping the time control and which side is at the bottom
+	pub fn initialize_pieces(&mut self) {
+		self.board.fresh_game();
+
+		let clock = self.clocks[&ChessPieceColor::White];
+		self.set_time_control(clock.get_remaining(), clock.get_increment());
+		self.side_to_move = ChessPieceColor::White;
+		self.history.clear();
+		self.result = None;
+		self.castling_rights = self.get_default_castling_rights();
+		self.en_passant = None;
+		self.halfmove_clock = 0;
+		self.fullmove_number = 1;
+		for pocket in self.pockets.values_mut() {
+			pocket.clear();
+		}
+		self.promoted.clear();
+		for checks in self.checks_given.values_mut() {
+			*checks = 0;
+		}
+	}
+
+	// A game continuing from the pieces on `board`, shown with white at the bottom. Castling rights,
+	// en passant and the move counters are left for the caller to set.
+	pub fn from_board(board: ChessBoard<ChessPiece>, side_to_move: ChessPieceColor) -> ChessGame {
+		let mut game = ChessGame::new(ChessPieceColor::White);
+		game.board = board;
+		game.side_to_move = side_to_move;
+		game.result = game.get_position_result();
+		game
+	}
+
+	// The row a color's pieces start on
+	pub fn get_home_row(&self, color: ChessPieceColor) -> u8 {
+		match color {
+			ChessPieceColor::White => 0,
+			ChessPieceColor::Black => self.board.get_height() - 1
+		}
+	}
+
+	// Castling with the outermost rook on each side of the king, for kings and rooks on their home row
+	pub fn get_default_castling_rights(&self) -> Vec<CastlingRight> {
+		let mut rights = Vec::new();
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			let row = self.get_home_row(color);
+			let king = ChessPiece::new(ChessPieceType::King, color);
+			let rook = ChessPiece::new(ChessPieceType::Rook, color);
+			let pieces_on_row: Vec<(u8, ChessPiece)> = (0..self.board.get_width())
+				.filter_map(|col| match self.board.get_contents_at_coordinates(&(col, row).into()) {
+					Ok(&Some(piece)) => Some((col, piece)),
+					_ => None
+				})
+				.collect();
+
+			let king_col = match pieces_on_row.iter().find(|&&(_, piece)| piece == king) {
+				Some(&(col, _)) => col,
+				None => { continue; }
+			};
+
+			let queen_side = pieces_on_row.iter().find(|&&(col, piece)| piece == rook && col < king_col);
+			let king_side = pieces_on_row.iter().rev().find(|&&(col, piece)| piece == rook && col > king_col);
+			for &&(col, _) in queen_side.iter().chain(king_side.iter()) {
+				rights.push(CastlingRight { color: color, rook: (col, row).into() });
+			}
+		}
+
+		rights
+	}
+
+	// Whether the king and the rook of a castling right are both on their home row
+	pub fn is_castling_right_possible(&self, right: &CastlingRight) -> bool {
+		let row = self.get_home_row(right.color);
+		let king = ChessPiece::new(ChessPieceType::King, right.color);
+		let rook = ChessPiece::new(ChessPieceType::Rook, right.color);
+		right.rook.row == row &&
+			self.board.get_contents_at_coordinates(&right.rook) == Ok(&Some(rook)) &&
+			(0..self.board.get_width()).any(|col| self.board.get_contents_at_coordinates(&(col, row).into()) == Ok(&Some(king)))
+	}
+
+	// Whether a castling right is one of standard chess, the king on the e-file and the rook in a corner
+	pub fn is_standard_castling_right(&self, right: &CastlingRight) -> bool {
+		let row = self.get_home_row(right.color);
+		let king = ChessPiece::new(ChessPieceType::King, right.color);
+		let last_col = self.board.get_width() - 1;
+		(right.rook.col == 0 || right.rook.col == last_col) &&
+			self.board.get_contents_at_coordinates(&(self.board.get_width() / 2, row).into()) == Ok(&Some(king))
+	}
+
+	pub fn get_castling_rights(&self) -> &Vec<CastlingRight> {
+		&self.castling_rights
+	}
+
+	pub fn set_castling_rights(&mut self, rights: Vec<CastlingRight>) {
+		self.castling_rights = rights;
+	}
+
+	pub fn get_en_passant(&self) -> Option<BoardCoordinates> {
+		self.en_passant
+	}
+
+	pub fn set_en_passant(&mut self, en_passant: Option<BoardCoordinates>) {
+		self.en_passant = en_passant;
+	}
+
+	pub fn get_halfmove_clock(&self) -> u32 {
+		self.halfmove_clock
+	}
+
+	pub fn get_fullmove_number(&self) -> u32 {
+		self.fullmove_number
+	}
+
+	pub fn set_move_counters(&mut self, halfmove_clock: u32, fullmove_number: u32) {
+		self.halfmove_clock = halfmove_clock;
+		self.fullmove_number = fullmove_number;
+	}
+
+	// Works the result out again once a position set up field by field is complete, as castling
+	// and en passant can give the side to move its only legal moves
+	pub fn update_position_result(&mut self) {
+		self.result = self.get_position_result();
+	}
+
+	pub fn is_chess960(&self) -> bool {
+		self.chess960
+	}
+
+	pub fn set_chess960(&mut self, chess960: bool) {
+		self.chess960 = chess960;
+	}
+
+	pub fn get_piece_definitions(&self) -> &Vec<PieceDefinition> {
+		&self.pieces
+	}
+
+	pub fn get_piece_definition(&self, letter: char) -> Option<&PieceDefinition> {
+		self.pieces.iter().find(|definition| definition.get_letter() == letter)
+	}
+
+	// Lets fairy pieces written with the definition's letter move on this game's board
+	pub fn add_piece_definition(&mut self, definition: PieceDefinition) -> Result<(), ChessError> {
+		if self.get_piece_definition(definition.get_letter()).is_some() {
+			return Err(ChessError::InvalidPiece(format!("There is already a piece written '{}'", definition.get_letter())));
+		}
+		self.pieces.push(definition);
+		Ok(())
+	}
+
+	// Material value in pawns, fairy pieces taking theirs from their definition
+	pub fn get_piece_value(&self, type_name: ChessPieceType) -> u32 {
+		match type_name {
+			ChessPieceType::Fairy(letter) => self.get_piece_definition(letter).map_or(0, |definition| definition.get_value()),
+			_ => type_name.get_value()
+		}
+	}
+
+	// The rules this game is played by, e.g. those of the variant it was set up from
+	pub fn get_rules(&self) -> &Rules {
+		&self.rules
+	}
+
+	// Plays on by other rules, e.g. those of a variant. Castling rights are dropped when the rules
+	// have no castling.
+	pub fn set_rules(&mut self, rules: Rules) {
+		if !rules.castling {
+			self.castling_rights.clear();
+		}
+		self.rules = rules;
+		self.result = self.get_position_result();
+	}
+
+	// The pieces `color` holds to drop, most valuable first
+	pub fn get_pocket(&self, color: ChessPieceColor) -> &Vec<ChessPieceType> {
+		&self.pockets[&color]
+	}
+
+	pub fn set_pocket(&mut self, color: ChessPieceColor, mut pieces: Vec<ChessPieceType>) {
+		pieces.sort_by(|a, b| {
+			self.get_piece_value(*b).cmp(&self.get_piece_value(*a))
+				.then(ChessPiece::new(*a, color).to_letter().cmp(&ChessPiece::new(*b, color).to_letter()))
+		});
+		self.pockets.insert(color, pieces);
+	}
+
+	pub fn add_to_pocket(&mut self, color: ChessPieceColor, type_name: ChessPieceType) {
+		let mut pieces = self.pockets[&color].clone();
+		pieces.push(type_name);
+		self.set_pocket(color, pieces);
+	}
+
+	// Whether the piece on a square was promoted from a pawn
+	pub fn is_promoted(&self, coordinates: &BoardCoordinates) -> bool {
+		self.promoted.contains(coordinates)
+	}
+
+	pub fn set_promoted(&mut self, squares: Vec<BoardCoordinates>) {
+		self.promoted = squares;
+	}
+
+	// Checks `color` still has to give to win Three-check
+	pub fn get_remaining_checks(&self, color: ChessPieceColor) -> u8 {
+		CHECKS_TO_WIN.saturating_sub(self.checks_given[&color])
+	}
+
+	pub fn set_remaining_checks(&mut self, color: ChessPieceColor, remaining: u8) {
+		self.checks_given.insert(color, CHECKS_TO_WIN.saturating_sub(remaining));
+		self.result = self.get_position_result();
+	}
+
+	// Whether a square is one of the centre squares a king wins King of the Hill on, d4, e4, d5 and
+	// e5 on the usual board. Boards with an odd number of files or ranks have a narrower hill.
+	pub fn is_hill_square(&self, coordinates: &BoardCoordinates) -> bool {
+		let (width, height) = self.board.get_dimensions();
+		coordinates.col >= (width - 1) / 2 && coordinates.col <= width / 2 &&
+			coordinates.row >= (height - 1) / 2 && coordinates.row <= height / 2
+	}
+
+	// The color whose side of the board is drawn at the bottom
+	pub fn get_bottom_player_color(&self) -> ChessPieceColor {
+		self.bottom_color
+	}
+
+	pub fn set_bottom_player_color(&mut self, bottom_color: ChessPieceColor) {
+		self.bottom_color = bottom_color;
+	}
+
+	// Whether the board is drawn turned round, with black's side at the bottom
+	pub fn is_flipped(&self) -> bool {
+		self.bottom_color == ChessPieceColor::Black
+	}
+
+	// Resets both clocks to the given time control
+	pub fn set_time_control(&mut self, initial_seconds: f64, increment_seconds: f64) {
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			self.clocks.insert(color, ChessClock::new(initial_seconds, increment_seconds));
+		}
+	}
+
+	// The legal move from the start to the end square of `chess_move`, leaving out any promotion.
+	// Castling is matched by the king's end square, so callers do not need to fill in the rook, or by
+	// the king moving onto its own rook as in Chess960. When a king could either step or castle to
+	// the same square, as can happen in Chess960, it steps unless the rook is given. Drops are matched
+	// by the piece and the square it lands on.
+	pub fn find_legal_move(&self, chess_move: &ChessMove) -> Result<Option<ChessMove>, ChessError> {
+		let end = chess_move.end_coordinates;
+		if chess_move.drop.is_some() {
+			try!(self.board.get_square(&end));
+			return Ok(self.get_drop_moves().into_iter()
+				.find(|legal_move| legal_move.end_coordinates == end && legal_move.drop == chess_move.drop));
+		}
+
+		let candidates: Vec<ChessMove> = try!(self.get_legal_moves(&chess_move.start_coordinates)).into_iter()
+			.filter(|legal_move| legal_move.end_coordinates == end || legal_move.castling_rook == Some(end))
+			.collect();
+
+		Ok(candidates.iter()
+			.find(|legal_move| legal_move.castling_rook == chess_move.castling_rook)
+			.or(candidates.first())
+			.cloned())
+	}
+
+	// Plays a legal move for the side to move, see `find_legal_move` for how castling is matched
+	pub fn move_piece(&mut self, chess_move: &ChessMove) -> Result<(), ChessError> {
+		if self.result.is_some() {
+			return Err(ChessError::GameOver);
+		}
+		if chess_move.drop.is_none() && try!(self.board.get_contents_at_coordinates(&chess_move.start_coordinates)).is_none() {
+			return Err(ChessError::EmptySquare(chess_move.start_coordinates));
+		}
+		try!(self.board.get_contents_at_coordinates(&chess_move.end_coordinates));
+
+		let legal_move = try!(self.find_legal_move(chess_move)).and_then(|legal_move| {
+			self.with_promotion_choices(vec![legal_move]).into_iter()
+				.find(|choice| choice.promotion == chess_move.promotion)
+		});
+		let chess_move = match legal_move {
+			Some(legal_move) => legal_move,
+			None => { return Err(ChessError::IllegalMove(self.get_coordinate_notation(chess_move))); }
 		};
 
-		match up_color {
-			ChessPieceColor::Black => {
-				game.colors_directions.insert(ChessPieceColor::Black, Direction::Up);
-				game.colors_directions.insert(ChessPieceColor::White, Direction::Down);
+		let mut notation = self.get_algebraic_notation(&chess_move);
+		let captured_promoted = self.promoted.contains(&chess_move.end_coordinates);
+		let (piece, captured) = try!(self.apply_move(&chess_move));
+		let captured_as = captured.map(|captured| if captured_promoted { ChessPieceType::Pawn } else { captured.get_type() });
+
+		if let Some(clock) = self.clocks.get_mut(&piece.get_color()) {
+			clock.add_increment();
+		}
+		self.result = self.get_position_result();
+
+		if self.is_in_check(self.side_to_move) {
+			notation.push(if self.has_legal_moves(self.side_to_move) { '+' } else { '#' });
+		}
+
+		self.history.push(MoveRecord {
+			chess_move: chess_move,
+			piece: piece,
+			captured: captured,
+			captured_as: captured_as,
+			notation: notation
+		});
+
+		Ok(())
+	}
+
+	// Plays a move on the board and passes the turn, without recording it or looking for the end of
+	// the game. Used when searching ahead, where that bookkeeping would only slow things down.
+	// Returns the piece that moved and the piece it captured.
+	pub fn apply_move(&mut self, chess_move: &ChessMove) -> Result<(ChessPiece, Option<ChessPiece>), ChessError> {
+		let start = chess_move.start_coordinates;
+		let end = chess_move.end_coordinates;
+		let piece = match chess_move.drop {
+			Some(type_name) => {
+				let color = self.side_to_move;
+				let index = match self.pockets[&color].iter().position(|&pocketed| pocketed == type_name) {
+					Some(index) => index,
+					None => { return Err(ChessError::IllegalMove(self.get_coordinate_notation(chess_move))); }
+				};
+				if try!(self.board.get_contents_at_coordinates(&end)).is_some() {
+					return Err(ChessError::IllegalMove(self.get_coordinate_notation(chess_move)));
+				}
+				self.pockets.get_mut(&color).unwrap().remove(index);
+				ChessPiece::new(type_name, color)
 			},
-			ChessPieceColor::White => {
-				game.colors_directions.insert(ChessPieceColor::White, Direction::Up);
-				game.colors_directions.insert(ChessPieceColor::Black, Direction::Down);
+			None => match try!(self.board.get_contents_at_coordinates(&start)) {
+				&Some(piece) => piece,
+				&None => { return Err(ChessError::EmptySquare(start)); }
+			}
+		};
+		let captured = try!(ChessGame::play_on_board(&mut self.board, chess_move, piece.get_color()));
+		let exploded = match captured {
+			Some(..) if self.rules.explosions => try!(ChessGame::explode(&mut self.board, &end)),
+			_ => Vec::new()
+		};
+
+		// With drops the captured piece goes to the capturer's pocket, as a pawn if it was promoted. In
+		// bughouse it goes to the partner instead, which is left to whoever runs the two boards.
+		if self.rules.drops {
+			let was_promoted = chess_move.drop.is_none() && self.promoted.contains(&start);
+			match captured {
+				Some(captured) if !self.rules.captures_to_partner => {
+					let type_name = if self.promoted.contains(&end) { ChessPieceType::Pawn } else { captured.get_type() };
+					self.add_to_pocket(piece.get_color(), type_name);
+				},
+				_ => {}
+			}
+			self.promoted.retain(|&square| square != start && square != end);
+			if was_promoted || chess_move.promotion.is_some() {
+				self.promoted.push(end);
 			}
 		}
 
-		game
+		// Castling rights are lost once the king or the rook moves, or the rook is captured or blown up
+		self.castling_rights.retain(|right| {
+			!(piece.get_type() == ChessPieceType::King && right.color == piece.get_color()) &&
+				right.rook != start && right.rook != end && !exploded.contains(&right.rook)
+		});
+		if !exploded.is_empty() {
+			self.promoted.retain(|square| !exploded.contains(square));
+			let kings_left: Vec<ChessPieceColor> = [ChessPieceColor::White, ChessPieceColor::Black].iter().cloned()
+				.filter(|&color| self.find_king(&self.board, color).is_some())
+				.collect();
+			self.castling_rights.retain(|right| kings_left.contains(&right.color));
+		}
+
+		self.en_passant = None;
+		if piece.get_type() == ChessPieceType::Pawn && (end.row as i8 - start.row as i8).abs() == 2 {
+			self.en_passant = Some(BoardCoordinates { col: start.col, row: (start.row + end.row) / 2 });
+		}
+
+		if piece.get_type() == ChessPieceType::Pawn || captured.is_some() {
+			self.halfmove_clock = 0;
+		} else {
+			self.halfmove_clock += 1;
+		}
+		if piece.get_color() == ChessPieceColor::Black {
+			self.fullmove_number += 1;
+		}
+
+		self.side_to_move = piece.get_color().opposite_color();
+		if self.rules.win_conditions.contains(&WinCondition::ThreeCheck) && self.is_in_check(self.side_to_move) {
+			*self.checks_given.get_mut(&piece.get_color()).unwrap() += 1;
+		}
+		Ok((piece, captured))
 	}
 
-	pub fn initialize_pieces(&mut self) {
-		self.board.fresh_game(ChessPieceColor::Black);
+	// Moves the pieces for `chess_move` on `board`, including the rook when castling and the pawn
+	// taken en passant, and returns the captured piece. `color` is the side playing it, which is
+	// only needed to know whose piece a drop puts down.
+	fn play_on_board(board: &mut ChessBoard<ChessPiece>, chess_move: &ChessMove, color: ChessPieceColor) -> Result<Option<ChessPiece>, ChessError> {
+		let start = chess_move.start_coordinates;
+		let end = chess_move.end_coordinates;
+		if let Some(type_name) = chess_move.drop {
+			try!(board.set_contents_at_coordinates(&end, Some(ChessPiece::new(type_name, color))));
+			return Ok(None);
+		}
+
+		let piece = match try!(board.get_contents_at_coordinates(&start)) {
+			&Some(piece) => piece,
+			&None => { return Err(ChessError::EmptySquare(start)); }
+		};
+
+		if let Some(rook_coordinates) = chess_move.castling_rook {
+			// Both pieces are lifted first, since either may land where the other started
+			let rook = try!(board.get_contents_at_coordinates(&rook_coordinates)).clone();
+			let rook_col = if rook_coordinates.col > start.col { end.col - 1 } else { end.col + 1 };
+			try!(board.set_contents_at_coordinates(&start, None));
+			try!(board.set_contents_at_coordinates(&rook_coordinates, None));
+			try!(board.set_contents_at_coordinates(&end, Some(piece)));
+			try!(board.set_contents_at_coordinates(&(rook_col, start.row).into(), rook));
+			return Ok(None);
+		}
+
+		let mut captured = try!(board.get_contents_at_coordinates(&end)).clone();
+
+		// A pawn moving diagonally onto an empty square takes the pawn beside it en passant
+		if piece.get_type() == ChessPieceType::Pawn && start.col != end.col && captured.is_none() {
+			let passed = BoardCoordinates { col: end.col, row: start.row };
+			captured = try!(board.get_contents_at_coordinates(&passed)).clone();
+			try!(board.set_contents_at_coordinates(&passed, None));
+		}
+
+		try!(board.move_contents(&start, &end));
+
+		if let Some(promotion) = chess_move.promotion {
+			try!(board.set_contents_at_coordinates(&end, Some(ChessPiece::new(promotion, piece.get_color()))));
+		}
+
+		Ok(captured)
 	}
 
-	// Temporarily public for testing stuff
-	pub fn set_contents(&mut self, piece: Option<ChessPiece>, coordinates: &BoardCoordinates) {
-		self.board.set_contents_at_coordinates(coordinates, piece);
+	// Blows up everything around a capture on `centre` apart from pawns, along with the capturing
+	// piece itself, as in atomic chess. Returns the squares that were emptied.
+	fn explode(board: &mut ChessBoard<ChessPiece>, centre: &BoardCoordinates) -> Result<Vec<BoardCoordinates>, ChessError> {
+		let mut exploded = vec![*centre];
+		for &(horzontal, vertical) in &ADJACENT_OFFSETS {
+			match board.get_move_destination(centre, horzontal, vertical) {
+				Some(cell) => match cell.get_contents() {
+					&Some(piece) if piece.get_type() != ChessPieceType::Pawn => { exploded.push(*cell.get_coordinates()); },
+					_ => {}
+				},
+				None => {}
+			}
+		}
+
+		for coordinates in &exploded {
+			try!(board.set_contents_at_coordinates(coordinates, None));
+		}
+		Ok(exploded)
 	}
 
-	pub fn move_piece(&mut self, chess_move: &ChessMove) {
-		self.board.move_contents(&chess_move.start_coordinates, &chess_move.end_coordinates);
+	// Checkmate or stalemate for the side to move, if either has happened, or a win by the other
+	// conditions of the rules. What stalemate means, and whether checkmate ends the game, is up to
+	// the rules.
+	pub fn get_position_result(&self) -> Option<GameResult> {
+		let variant_result = self.get_variant_result();
+		if variant_result.is_some() {
+			return variant_result;
+		}
+
+		let side_to_move = self.side_to_move;
+		if self.has_legal_moves(side_to_move) {
+			return None;
+		}
+
+		if self.rules.win_conditions.contains(&WinCondition::Checkmate) && self.is_in_check(side_to_move) {
+			return Some(GameResult::Win(side_to_move.opposite_color(), WinReason::Checkmate));
+		}
+
+		// Without a royal king a player can run out of pieces, which counts as being stalemated
+		let has_pieces = self.board.get_all_coordinates().iter().any(|coordinates| match self.board.get_contents_at_coordinates(coordinates) {
+			Ok(&Some(piece)) => piece.get_color() == side_to_move,
+			_ => false
+		});
+		let reason = if has_pieces { WinReason::Stalemate } else { WinReason::AllPiecesLost };
+		match self.rules.stalemate {
+			StalemateRule::Draw => Some(GameResult::Draw(DrawReason::Stalemate)),
+			StalemateRule::Win => Some(GameResult::Win(side_to_move, reason)),
+			StalemateRule::Loss => Some(GameResult::Win(side_to_move.opposite_color(), reason))
+		}
 	}
 
-	pub fn get_legal_moves(&self, current_pos: &BoardCoordinates) -> Vec<ChessMove> {
+	// A win that does not depend on the moves left: a king blown up in atomic chess, a king on the
+	// hill or a third check given. Only the player who just moved can have won this way.
+	pub fn get_variant_result(&self) -> Option<GameResult> {
+		let mover = self.side_to_move.opposite_color();
+		if self.rules.explosions && self.find_king(&self.board, self.side_to_move).is_none() {
+			return Some(GameResult::Win(mover, WinReason::Explosion));
+		}
+
+		for condition in &self.rules.win_conditions {
+			match condition {
+				&WinCondition::KingOfTheHill => {
+					if self.find_king(&self.board, mover).map_or(false, |coordinates| self.is_hill_square(&coordinates)) {
+						return Some(GameResult::Win(mover, WinReason::KingOfTheHill));
+					}
+				},
+				&WinCondition::ThreeCheck => {
+					if self.get_remaining_checks(mover) == 0 {
+						return Some(GameResult::Win(mover, WinReason::ThreeChecks));
+					}
+				},
+				&WinCondition::Checkmate => {}
+			}
+		}
+
+		None
+	}
+
+	// Runs the clock of the side to move. Clocks start once the first move has been played.
+	pub fn update(&mut self, seconds: f64) {
+		if self.result.is_some() || self.history.is_empty() {
+			return;
+		}
+
+		let side_to_move = self.side_to_move;
+		let flagged = match self.clocks.get_mut(&side_to_move) {
+			Some(clock) => {
+				clock.tick(seconds);
+				clock.is_flagged()
+			},
+			None => false
+		};
+
+		if flagged {
+			self.result = Some(GameResult::Win(side_to_move.opposite_color(), WinReason::Timeout));
+		}
+	}
+
+	pub fn get_side_to_move(&self) -> ChessPieceColor {
+		self.side_to_move
+	}
+
+	pub fn get_history(&self) -> &Vec<MoveRecord> {
+		&self.history
+	}
+
+	pub fn get_clock(&self, color: ChessPieceColor) -> &ChessClock {
+		&self.clocks[&color]
+	}
+
+	pub fn get_result(&self) -> Option<GameResult> {
+		self.result
+	}
+
+	// Ends the game for a reason outside the position on its board, e.g. the other bughouse board.
+	// A game that is already over keeps its result.
+	pub fn end(&mut self, result: GameResult) {
+		if self.result.is_none() {
+			self.result = Some(result);
+		}
+	}
+
+	// The pieces `color` has taken from the opponent, in the order they were captured
+	pub fn get_captured_pieces(&self, color: ChessPieceColor) -> Vec<ChessPiece> {
+		self.history.iter()
+			.filter(|record| record.piece.get_color() == color)
+			.filter_map(|record| record.captured)
+			.collect()
+	}
+
+	// Material on the board for `color`, minus the opponent's
+	pub fn get_material_difference(&self, color: ChessPieceColor) -> i32 {
+		let mut difference = 0i32;
+		for coordinates in self.board.get_all_coordinates() {
+			if let Ok(&Some(piece)) = self.board.get_contents_at_coordinates(&coordinates) {
+				let value = self.get_piece_value(piece.get_type()) as i32;
+				difference += if piece.get_color() == color { value } else { -value };
+			}
+		}
+
+		difference
+	}
+
+	// Whether a move takes a pawn into the promotion ranks, meaning a promotion piece has to be
+	// picked. Before the last rank the pawn may also stay a pawn, see `is_promotion_optional`.
+	pub fn is_promotion_move(&self, chess_move: &ChessMove) -> bool {
+		self.get_ranks_to_promote(chess_move).map_or(false, |ranks| ranks < self.rules.promotion_ranks)
+	}
+
+	pub fn is_promotion_optional(&self, chess_move: &ChessMove) -> bool {
+		self.get_ranks_to_promote(chess_move).map_or(false, |ranks| ranks > 0 && ranks < self.rules.promotion_ranks)
+	}
+
+	// How many ranks short of the last one a pawn move ends, None for moves of other pieces
+	fn get_ranks_to_promote(&self, chess_move: &ChessMove) -> Option<u8> {
+		match self.board.get_contents_at_coordinates(&chess_move.start_coordinates) {
+			Ok(&Some(piece)) if piece.get_type() == ChessPieceType::Pawn => {
+				let row = chess_move.end_coordinates.row;
+				Some(match self.get_pawn_direction(piece.get_color()) {
+					1 => self.board.get_height() - 1 - row,
+					_ => row
+				})
+			},
+			_ => None
+		}
+	}
+
+	// Which way along the rows the pawns of a color advance, white's towards the last row
+	pub fn get_pawn_direction(&self, color: ChessPieceColor) -> i8 {
+		match color {
+			ChessPieceColor::White => 1i8,
+			ChessPieceColor::Black => -1i8
+		}
+	}
+
+	// Moves for the piece at `current_pos` that the side to move may actually play,
+	// i.e. that do not leave its own king in check, and only its captures when the rules force
+	// captures and the side to move has one.
+	// Empty when the square is empty, holds a piece of the other side or the game is over.
+	pub fn get_legal_moves(&self, current_pos: &BoardCoordinates) -> Result<Vec<ChessMove>, ChessError> {
+		let moves = try!(self.get_unforced_moves(current_pos));
+		if self.rules.forced_captures && self.can_capture() {
+			return Ok(moves.into_iter().filter(|chess_move| self.is_capture(chess_move)).collect());
+		}
+
+		Ok(moves)
+	}
+
+	// The legal moves of the piece at `current_pos` whether or not a capture is forced elsewhere
+	fn get_unforced_moves(&self, current_pos: &BoardCoordinates) -> Result<Vec<ChessMove>, ChessError> {
+		match try!(self.board.get_contents_at_coordinates(current_pos)) {
+			&Some(piece) if piece.get_color() == self.side_to_move && self.result.is_none() => {},
+			_ => { return Ok(Vec::new()); }
+		}
+
+		Ok(self.get_piece_moves(current_pos).into_iter()
+			.filter(|chess_move| !self.leaves_king_in_check(chess_move, self.side_to_move))
+			.collect())
+	}
+
+	// Every legal move for the side to move, drops included
+	pub fn get_all_legal_moves(&self) -> Vec<ChessMove> {
+		let mut result: Vec<ChessMove> = Vec::new();
+		for coordinates in self.board.get_all_coordinates() {
+			if let Ok(mut moves) = self.get_unforced_moves(&coordinates) {
+				result.append(&mut moves);
+			}
+		}
+		result.append(&mut self.get_drop_moves());
+
+		if self.rules.forced_captures && result.iter().any(|chess_move| self.is_capture(chess_move)) {
+			result.retain(|chess_move| self.is_capture(chess_move));
+		}
+		result
+	}
+
+	// Whether a move takes a piece, pawns taking en passant included
+	pub fn is_capture(&self, chess_move: &ChessMove) -> bool {
+		if chess_move.drop.is_some() || chess_move.castling_rook.is_some() {
+			return false;
+		}
+
+		match self.board.get_contents_at_coordinates(&chess_move.end_coordinates) {
+			Ok(&Some(..)) => true,
+			_ => match self.board.get_contents_at_coordinates(&chess_move.start_coordinates) {
+				Ok(&Some(piece)) => piece.get_type() == ChessPieceType::Pawn && chess_move.start_coordinates.col != chess_move.end_coordinates.col,
+				_ => false
+			}
+		}
+	}
+
+	// Whether the side to move has a legal capture anywhere on the board
+	fn can_capture(&self) -> bool {
+		self.board.get_all_coordinates().iter().any(|coordinates| {
+			self.get_unforced_moves(coordinates).map_or(false, |moves| moves.iter().any(|chess_move| self.is_capture(chess_move)))
+		})
+	}
+
+	// Drops the side to move may play, when the rules allow them
+	pub fn get_drop_moves(&self) -> Vec<ChessMove> {
+		if self.result.is_some() {
+			return Vec::new();
+		}
+
+		let color = self.side_to_move;
+		self.get_drop_candidates(color).into_iter()
+			.filter(|chess_move| !self.leaves_king_in_check(chess_move, color))
+			.collect()
+	}
+
+	// Each kind of piece in `color`'s pocket onto every empty square, apart from pawns onto the
+	// first and last ranks
+	fn get_drop_candidates(&self, color: ChessPieceColor) -> Vec<ChessMove> {
+		let mut result: Vec<ChessMove> = Vec::new();
+		if !self.rules.drops {
+			return result;
+		}
+
+		let mut types = self.pockets[&color].clone();
+		types.dedup();
+		let last_row = self.board.get_height() - 1;
+		for coordinates in self.board.get_all_coordinates() {
+			if self.board.get_contents_at_coordinates(&coordinates) != Ok(&None) {
+				continue;
+			}
+			for &type_name in &types {
+				if type_name == ChessPieceType::Pawn && (coordinates.row == 0 || coordinates.row == last_row) {
+					continue;
+				}
+				result.push(ChessMove::drop(type_name, &coordinates));
+			}
+		}
+
+		result
+	}
+
+	// Legal moves are listed without a promotion piece, this lists pawns reaching the promotion ranks
+	// once for each piece they can become, and once more unpromoted where promoting is optional
+	pub fn with_promotion_choices(&self, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+		let mut result: Vec<ChessMove> = Vec::new();
+		for chess_move in moves {
+			if self.is_promotion_move(&chess_move) && chess_move.promotion.is_none() {
+				result.extend(self.rules.promotion_pieces.iter().map(|&choice| chess_move.with_promotion(choice)));
+				if self.is_promotion_optional(&chess_move) {
+					result.push(chess_move);
+				}
+			} else {
+				result.push(chess_move);
+			}
+		}
+
+		result
+	}
+
+	fn has_legal_moves(&self, color: ChessPieceColor) -> bool {
+		let has_piece_moves = self.board.get_all_coordinates().iter().any(|coordinates| {
+			match self.board.get_contents_at_coordinates(coordinates) {
+				Ok(&Some(piece)) if piece.get_color() == color => {
+					self.get_piece_moves(coordinates).iter()
+						.any(|chess_move| !self.leaves_king_in_check(chess_move, color))
+				},
+				_ => false
+			}
+		});
+
+		// A drop may block a check that no move can get out of
+		has_piece_moves || self.get_drop_candidates(color).iter().any(|chess_move| !self.leaves_king_in_check(chess_move, color))
+	}
+
+	pub fn is_in_check(&self, color: ChessPieceColor) -> bool {
+		self.is_king_attacked(&self.board, color)
+	}
+
+	// Plays the move on a copy of the board and looks at whether `color`'s king is attacked afterwards.
+	// With explosions a move may not blow up its own king, but blowing up the other king wins even
+	// when it leaves the player's own in check.
+	fn leaves_king_in_check(&self, chess_move: &ChessMove, color: ChessPieceColor) -> bool {
+		let mut board = self.board.clone();
+		let captured = match ChessGame::play_on_board(&mut board, chess_move, color) {
+			Ok(captured) => captured,
+			Err(_) => { return true; } // A move that cannot be played on the board is never legal
+		};
+
+		if self.rules.explosions {
+			if captured.is_some() && ChessGame::explode(&mut board, &chess_move.end_coordinates).is_err() {
+				return true;
+			}
+			if self.find_king(&board, color).is_none() {
+				return true;
+			}
+			if self.find_king(&board, color.opposite_color()).is_none() {
+				return false;
+			}
+		}
+
+		self.is_king_attacked(&board, color)
+	}
+
+	fn find_king(&self, board: &ChessBoard<ChessPiece>, color: ChessPieceColor) -> Option<BoardCoordinates> {
+		let king = ChessPiece::new(ChessPieceType::King, color);
+		board.get_all_coordinates().into_iter()
+			.find(|coordinates| board.get_contents_at_coordinates(coordinates) == Ok(&Some(king)))
+	}
+
+	// Never true when the king is an ordinary piece, as in antichess
+	fn is_king_attacked(&self, board: &ChessBoard<ChessPiece>, color: ChessPieceColor) -> bool {
+		if !self.rules.royal_king {
+			return false;
+		}
+
+		self.find_king(board, color)
+			.map_or(false, |coordinates| self.is_square_attacked_for_king(board, &coordinates, color.opposite_color()))
+	}
+
+	// Whether a king standing on `target` would be in check from `by_color`. With explosions a king
+	// next to the other king can not be taken, since the capture would blow up the other king too.
+	fn is_square_attacked_for_king(&self, board: &ChessBoard<ChessPiece>, target: &BoardCoordinates, by_color: ChessPieceColor) -> bool {
+		if self.rules.explosions {
+			let king = ChessPiece::new(ChessPieceType::King, by_color);
+			let touches_king = ADJACENT_OFFSETS.iter().any(|&(horzontal, vertical)| {
+				board.get_move_destination(target, horzontal, vertical).map_or(false, |cell| cell.get_contents() == &Some(king))
+			});
+			if touches_king {
+				return false;
+			}
+		}
+
+		self.is_square_attacked(board, target, by_color)
+	}
+
+	// Whether any piece of color `by_color` could capture on `target`, looking outwards from the target square
+	fn is_square_attacked(&self, board: &ChessBoard<ChessPiece>, target: &BoardCoordinates, by_color: ChessPieceColor) -> bool {
+		let attacker_at = |horzontal: i8, vertical: i8, types: &[ChessPieceType]| {
+			match board.get_move_destination(target, horzontal, vertical) {
+				Some(cell) => match cell.get_contents() {
+					&Some(piece) => piece.get_color() == by_color && types.contains(&piece.get_type()),
+					&None => false
+				},
+				None => false
+			}
+		};
+
+		// Pawns capture towards their direction of travel, so look the opposite way
+		let pawn_direction = self.get_pawn_direction(by_color);
+		if attacker_at(-1, -pawn_direction, &[ChessPieceType::Pawn]) ||
+			attacker_at(1, -pawn_direction, &[ChessPieceType::Pawn])
+		{
+			return true;
+		}
+
+		for &(horzontal, vertical) in &KNIGHT_OFFSETS {
+			if attacker_at(horzontal, vertical, &[ChessPieceType::Knight]) {
+				return true;
+			}
+		}
+
+		for &(horzontal, vertical) in &ADJACENT_OFFSETS {
+			if attacker_at(horzontal, vertical, &[ChessPieceType::King]) {
+				return true;
+			}
+		}
+
+		if self.is_attacked_by_fairy_piece(board, target, by_color) {
+			return true;
+		}
+
+		for &(horzontal, vertical) in &ADJACENT_OFFSETS {
+			let sliders = if horzontal == 0 || vertical == 0 {
+				[ChessPieceType::Rook, ChessPieceType::Queen]
+			} else {
+				[ChessPieceType::Bishop, ChessPieceType::Queen]
+			};
+
+			let mut distance = 1i8;
+			loop {
+				match board.get_move_destination(target, horzontal * distance, vertical * distance) {
+					Some(cell) => match cell.get_contents() {
+						&Some(piece) => {
+							if piece.get_color() == by_color && sliders.contains(&piece.get_type()) {
+								return true;
+							}
+							break;
+						},
+						&None => { distance += 1; }
+					},
+					None => { break; }
+				}
+			}
+		}
+
+		false
+	}
+
+	// Every move the piece at `current_pos` could make by its movement rules, ignoring checks
+	fn get_piece_moves(&self, current_pos: &BoardCoordinates) -> Vec<ChessMove> {
 		let mut result: Vec<ChessMove> = Vec::new();
 		match self.board.get_contents_at_coordinates(current_pos) {
 			Ok(&Some(piece)) if piece.get_type() == ChessPieceType::Rook => {
@@ -64,15 +1056,25 @@ impl ChessGame {
 				result.append(&mut self.get_vertical_moves(&piece, current_pos));
 				result.append(&mut self.get_horzontal_moves(&piece, current_pos));
 			},
+			// With explosions kings can not capture, as they would blow themselves up
+			Ok(&Some(piece)) if piece.get_type() == ChessPieceType::King && self.rules.explosions => {
+				let board = &self.board;
+				result.extend(self.get_adjacent_moves(&piece, current_pos).into_iter()
+					.filter(|chess_move| board.get_contents_at_coordinates(&chess_move.end_coordinates) == Ok(&None)));
+				result.append(&mut self.get_castling_moves(&piece, current_pos));
+			},
 			Ok(&Some(piece)) if piece.get_type() == ChessPieceType::King => {
-				result.append(&mut self.get_adjacent_moves(&piece, current_pos));	
+				result.append(&mut self.get_adjacent_moves(&piece, current_pos));
+				result.append(&mut self.get_castling_moves(&piece, current_pos));
 			},
 			Ok(&Some(piece)) if piece.get_type() == ChessPieceType::Pawn => {
 				result.append(&mut self.get_pawn_moves(&piece, current_pos));
 			},
-			Ok(&Some(..)) => { /* Some other piece type I guess */ },
+			Ok(&Some(piece)) => {
+				result.append(&mut self.get_fairy_moves(&piece, current_pos));
+			},
 			Ok(&None) => { /* Space was empty */ },
-			Err(e) => panic!("{}", e)
+			Err(_) => { /* Off the board, so nothing can move from there */ }
 		}
 
 		result
@@ -80,47 +1082,41 @@ impl ChessGame {
 
 	fn get_vertical_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
 		let mut result: Vec<ChessMove> = Vec::new();
-
-		let mut vertical_move: i8 = 0;
-		
-		loop {
-			vertical_move += 1;
-			match self.get_valid_move(piece, start_coordinates, 0, vertical_move) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
-			}
-		}
-
-		vertical_move = 0;
-		loop {
-			vertical_move -= 1;
-			match self.get_valid_move(piece, start_coordinates, 0, vertical_move) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
-			}
-		}
-
+		result.append(&mut self.get_sliding_moves(piece, start_coordinates, 0, 1));
+		result.append(&mut self.get_sliding_moves(piece, start_coordinates, 0, -1));
 		result
 	}
 
 	fn get_horzontal_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
 		let mut result: Vec<ChessMove> = Vec::new();
+		result.append(&mut self.get_sliding_moves(piece, start_coordinates, 1, 0));
+		result.append(&mut self.get_sliding_moves(piece, start_coordinates, -1, 0));
+		result
+	}
 
-		let mut horzontal_move: i8 = 0;
-
-		loop {
-			horzontal_move += 1;
-			match self.get_valid_move(piece, start_coordinates, horzontal_move, 0) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
-			}
+	fn get_diagonal_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
+		let mut result: Vec<ChessMove> = Vec::new();
+		for &(horzontal_step, vertical_step) in &[(1i8, 1i8), (-1, -1), (1, -1), (-1, 1)] {
+			result.append(&mut self.get_sliding_moves(piece, start_coordinates, horzontal_step, vertical_step));
 		}
+		result
+	}
+
+	// Moves along one line until the edge of the board, a piece of the same color or a capture
+	fn get_sliding_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates, horzontal_step: i8, vertical_step: i8) -> Vec<ChessMove> {
+		let mut result: Vec<ChessMove> = Vec::new();
 
-		horzontal_move = 0;
+		let mut distance: i8 = 0;
 		loop {
-			horzontal_move -= 1;
-			match self.get_valid_move(piece, start_coordinates, horzontal_move, 0) {
-				Some(chess_move) => { result.push(chess_move); },
+			distance += 1;
+			match self.get_valid_move(piece, start_coordinates, horzontal_step * distance, vertical_step * distance) {
+				Some(chess_move) => {
+					let is_capture = self.board.get_contents_at_coordinates(&chess_move.end_coordinates) != Ok(&None);
+					result.push(chess_move);
+					if is_capture {
+						break;
+					}
+				},
 				None => { break; }
 			}
 		}
@@ -128,73 +1124,66 @@ impl ChessGame {
 		result
 	}
 
-	fn get_diagonal_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
+	fn get_adjacent_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
 		let mut result: Vec<ChessMove> = Vec::new();
-
-		let mut horzontal_move: i8 = 0;
-		let mut vertical_move: i8 = 0;
-
-		loop {
-			horzontal_move += 1;
-			vertical_move += 1;
+		for &(horzontal_move, vertical_move) in &ADJACENT_OFFSETS {
 			match self.get_valid_move(piece, start_coordinates, horzontal_move, vertical_move) {
 				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
+				None => {}
 			}
 		}
 
-		horzontal_move = 0;
-		vertical_move = 0;
-		loop {
-			horzontal_move -= 1;
-			vertical_move -= 1;
-			match self.get_valid_move(piece, start_coordinates, horzontal_move, vertical_move) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
-			}
-		}
+		result
+	}
 
-		horzontal_move = 0;
-		vertical_move = 0;
-		loop {
-			horzontal_move += 1;
-			vertical_move -= 1;
-			match self.get_valid_move(piece, start_coordinates, horzontal_move, vertical_move) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
-			}
+	// Castling with each rook the king still has the right to castle with. The king ends up on the
+	// column next to the edge (g) or two in (c) with the rook on its inside, as in Chess960.
+	fn get_castling_moves(&self, piece: & ChessPiece, king_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
+		let mut result: Vec<ChessMove> = Vec::new();
+		let color = piece.get_color();
+		let row = king_coordinates.row;
+		if !self.rules.castling || self.is_square_attacked_for_king(&self.board, king_coordinates, color.opposite_color()) {
+			return result;
 		}
 
-		horzontal_move = 0;
-		vertical_move = 0;
-		loop {
-			horzontal_move -= 1;
-			vertical_move += 1;
-			match self.get_valid_move(piece, start_coordinates, horzontal_move, vertical_move) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
+		for right in self.castling_rights.iter().filter(|right| right.color == color && right.rook.row == row) {
+			let rook = right.rook;
+			if self.board.get_contents_at_coordinates(&rook) != Ok(&Some(ChessPiece::new(ChessPieceType::Rook, color))) {
+				continue;
 			}
-		}
 
-		result
-	}
+			let king_side = rook.col > king_coordinates.col;
+			let king_end_col = if king_side { self.board.get_width() - 2 } else { 2 };
+			let rook_end_col = if king_side { king_end_col - 1 } else { king_end_col + 1 };
 
-	fn get_adjacent_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
-		let mut result: Vec<ChessMove> = Vec::new();
-		for &(horzontal_move, vertical_move) in &[
-			(-1i8, -1i8),
-			(-1i8, 1i8),
-			(-1i8, 0i8),
-			(-1i8, -1i8),
-			(1i8, 1i8),
-			(1i8, 0i8),
-			(0i8, -1i8),
-			(0i8, 1i8)
-		] {
-			match self.get_valid_move(piece, start_coordinates, horzontal_move, vertical_move) {
-				Some(chess_move) => { result.push(chess_move); },
-				None => { break; }
+			// Every square either piece crosses or lands on must be empty apart from the two of them
+			let cols = [king_coordinates.col, rook.col, king_end_col, rook_end_col];
+			let low = *cols.iter().min().unwrap();
+			let high = *cols.iter().max().unwrap();
+			let blocked = (low..high + 1).any(|col| {
+				let coordinates: BoardCoordinates = (col, row).into();
+				coordinates != *king_coordinates && coordinates != rook &&
+					self.board.get_contents_at_coordinates(&coordinates) != Ok(&None)
+			});
+			if blocked {
+				continue;
+			}
+
+			// Nor may the king pass through check. It is lifted first so it does not shield the squares
+			// it crosses, which matters when a king next to the other one is not in check. Where it
+			// lands is left to the usual test for check, once the rook has moved too.
+			let mut board = self.board.clone();
+			let _ = board.set_contents_at_coordinates(king_coordinates, None);
+			let low = cmp::min(king_coordinates.col, king_end_col);
+			let high = cmp::max(king_coordinates.col, king_end_col);
+			let passes_check = (low..high + 1).filter(|&col| col != king_end_col).any(|col| {
+				self.is_square_attacked_for_king(&board, &(col, row).into(), color.opposite_color())
+			});
+			if passes_check {
+				continue;
 			}
+
+			result.push(ChessMove::castling(king_coordinates, &(king_end_col, row).into(), &rook));
 		}
 
 		result
@@ -202,16 +1191,7 @@ impl ChessGame {
 
 	fn get_knight_moves(&self, piece: & ChessPiece, start_coordinates: &BoardCoordinates) -> Vec<ChessMove> {
 		let mut result: Vec<ChessMove> = Vec::new();
-		for &(horzontal_move, vertical_move) in &[
-			(-2i8, -1i8),
-			(-2i8, 1i8),
-			(2i8, -1i8),
-			(2i8, 1i8),
-			(-1i8, -2i8),
-			(-1i8, 2i8),
-			(1i8, -2i8),
-			(1i8, 2i8)
-		] {
+		for &(horzontal_move, vertical_move) in &KNIGHT_OFFSETS {
 			match self.get_valid_move(piece, start_coordinates, horzontal_move, vertical_move) {
 				Some(chess_move) => { result.push(chess_move); },
 				None => {}
@@ -225,11 +1205,7 @@ impl ChessGame {
 
 		let mut result: Vec<ChessMove> = Vec::new();
 
-		let vertical_move: i8 = match self.colors_directions.get(&piece.get_color()) {
-			Some(&Direction::Up) => { -1i8 },
-			Some(&Direction::Down) => { 1i8 },
-			_ => { panic!("Colors to directions hasmap not initialized.") }
-		};
+		let vertical_move: i8 = self.get_pawn_direction(piece.get_color());
 
 		// Check forward move
 		match self.board.get_move_destination(start_coordinates, 0, vertical_move) {
@@ -242,7 +1218,9 @@ impl ChessGame {
 		}
 
 		// Result.len() == 1 means there is no piece directly in front of the pawn
-		let pawn_is_in_starting_position = true;
+		let double_step_rank = self.rules.double_step_rank;
+		let starting_row = if vertical_move > 0 { double_step_rank as i16 - 1 } else { self.board.get_height() as i16 - double_step_rank as i16 };
+		let pawn_is_in_starting_position = double_step_rank > 0 && start_coordinates.row as i16 == starting_row;
 		if pawn_is_in_starting_position && result.len() == 1 {
 		   match self.board.get_move_destination(start_coordinates, 0, vertical_move * 2) {
 				Some(board_cell) if board_cell.is_empty() => {
@@ -256,7 +1234,8 @@ impl ChessGame {
 
 		for &horzontal_move in &[-1i8, 1i8] {
 			match self.board.get_move_destination(start_coordinates, horzontal_move, vertical_move) {
-				Some(board_cell) if board_cell.contains_piece_of_color(piece.get_color()) => {
+				Some(board_cell) if board_cell.contains_piece_of_color(piece.get_color().opposite_color()) ||
+					Some(*board_cell.get_coordinates()) == self.en_passant => {
 					result.push(
 						ChessMove::new(start_coordinates, board_cell.get_coordinates())
 					);
@@ -288,9 +1267,83 @@ impl ChessGame {
 	}
 }
 
+#[cfg(feature = "serialization")]
+impl From<ChessGame> for SavedGame {
+	fn from(game: ChessGame) -> SavedGame {
+		SavedGame {
+			version: SCHEMA_VERSION,
+			board: game.board.into(),
+			bottom_color: game.bottom_color,
+			side_to_move: game.side_to_move,
+			history: game.history,
+			white_clock: game.clocks[&ChessPieceColor::White],
+			black_clock: game.clocks[&ChessPieceColor::Black],
+			result: game.result,
+			castling_rights: game.castling_rights,
+			en_passant: game.en_passant,
+			halfmove_clock: game.halfmove_clock,
+			fullmove_number: game.fullmove_number,
+			chess960: game.chess960,
+			pieces: game.pieces,
+			rules: game.rules,
+			white_pocket: game.pockets[&ChessPieceColor::White].clone(),
+			black_pocket: game.pockets[&ChessPieceColor::Black].clone(),
+			promoted: game.promoted,
+			white_checks: game.checks_given[&ChessPieceColor::White],
+			black_checks: game.checks_given[&ChessPieceColor::Black]
+		}
+	}
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<SavedGame> for ChessGame {
+	type Error = ChessError;
+
+	fn try_from(saved: SavedGame) -> Result<ChessGame, ChessError> {
+		if saved.version != SCHEMA_VERSION {
+			return Err(ChessError::UnsupportedVersion(saved.version));
+		}
+
+		let mut game = ChessGame::new(saved.bottom_color);
+		game.board = try!(ChessBoard::try_from(saved.board));
+
+		// Everything else that names a square has to be on the board too
+		for right in &saved.castling_rights {
+			try!(game.board.get_square(&right.rook));
+		}
+		if let Some(en_passant) = saved.en_passant {
+			try!(game.board.get_square(&en_passant));
+		}
+		for square in &saved.promoted {
+			try!(game.board.get_square(square));
+		}
+
+		game.side_to_move = saved.side_to_move;
+		game.history = saved.history;
+		game.clocks.insert(ChessPieceColor::White, saved.white_clock);
+		game.clocks.insert(ChessPieceColor::Black, saved.black_clock);
+		game.result = saved.result;
+		game.castling_rights = saved.castling_rights;
+		game.en_passant = saved.en_passant;
+		game.halfmove_clock = saved.halfmove_clock;
+		game.fullmove_number = saved.fullmove_number;
+		game.chess960 = saved.chess960;
+		game.pieces = saved.pieces;
+		game.rules = saved.rules;
+		game.set_pocket(ChessPieceColor::White, saved.white_pocket);
+		game.set_pocket(ChessPieceColor::Black, saved.black_pocket);
+		game.promoted = saved.promoted;
+		game.checks_given.insert(ChessPieceColor::White, saved.white_checks);
+		game.checks_given.insert(ChessPieceColor::Black, saved.black_checks);
+		Ok(game)
+	}
+}
+
 impl Renderable for ChessGame {
-	fn draw(&self, window: &PistonWindow) {
-		self.board.draw(window);
-	    // Maybe add some other stuff to draw later.
+	fn draw(&self, backend: &mut DrawBackend, theme: &Theme) {
+		let layout = BoardLayout::window(self.board.get_dimensions(), self.is_flipped());
+		draw_board_squares(backend, theme, &layout);
+		draw_board_coordinates(backend, theme, &layout, theme.coordinate_font_size);
+		self.board.draw_pieces(backend, &layout, None);
 	}
 }
\ No newline at end of file
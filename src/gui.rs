@@ -0,0 +1,164 @@
+use bughouse::BughouseController;
+use controller::ChessController;
+use editor::PositionEditor;
+use piston_backend::{Assets, PistonBackend};
+use piston_window::*;
+use session::{Session, autosave_path};
+use std::io::{self, Write};
+use theme::Theme;
+use view::{Renderable, resources_folder};
+
+// Size the window opens at. Boards too big for it are drawn with smaller squares.
+const WINDOW_SIZE: (u32, u32) = (1280, 720);
+
+// The window either plays a game or sets up a position to start a new one from
+enum Mode {
+	Play(ChessController),
+	Edit(PositionEditor)
+}
+
+// Plays the session's game in a window until it is closed, returning the session as it was left.
+// S saves it to the autosave file without closing the window, F turns the board round and E opens
+// the position editor.
+pub fn run_gui(session: Session) -> Session {
+	let mut session = session;
+	let window = open_window();
+	let themes = load_themes();
+	let mut theme_index = find_theme(&themes, session.theme.as_ref());
+
+	let mut assets = Assets::load(&window, themes[theme_index].clone());
+	let mut mode = Mode::Play(ChessController::new(session.game.clone()));
+	let mut window_size = (WINDOW_SIZE.0 as f64, WINDOW_SIZE.1 as f64);
+
+	for e in window {
+		if let Some(size) = e.resize_args() {
+			window_size = (size[0] as f64, size[1] as f64);
+		}
+
+		// E while playing opens the editor on the current position, the editor handles E itself to
+		// start playing from the edited position
+		let editor = match (&mode, e.press_args()) {
+			(&Mode::Play(ref controller), Some(Button::Keyboard(Key::E))) => Some(PositionEditor::from_game(controller.get_game())),
+			_ => None
+		};
+		match editor {
+			Some(editor) => { mode = Mode::Edit(editor); },
+			None => {
+				let edited_game = match mode {
+					Mode::Play(ref mut controller) => {
+						controller.event(&e);
+						None
+					},
+					Mode::Edit(ref mut editor) => editor.event(&e)
+				};
+				if let Some(game) = edited_game {
+					mode = Mode::Play(ChessController::new(game));
+				}
+			}
+		}
+
+		match e.press_args() {
+			Some(Button::Keyboard(Key::T)) => {
+				theme_index = (theme_index + 1) % themes.len();
+				assets.set_theme(&e, themes[theme_index].clone());
+			},
+			Some(Button::Keyboard(Key::F)) => {
+				if let Mode::Play(ref mut controller) = mode {
+					controller.flip_board();
+				}
+			},
+			// A position being edited is not a game yet, so the last game played is saved instead
+			Some(Button::Keyboard(Key::S)) => {
+				if let Mode::Play(ref controller) = mode {
+					session.game = controller.get_game().clone();
+				}
+				session.theme = Some(themes[theme_index].name.clone());
+				if let Some(path) = autosave_path() {
+					if let Err(error) = session.save(&path) {
+						let _ = writeln!(io::stderr(), "Could not save the game: {}", error);
+					}
+				}
+			},
+			_ => {}
+		}
+
+		e.draw_2d(|c, g| {
+            clear([1.0, 1.0, 1.0, 1.0], g);
+        });
+
+		// Fitted when drawn, so a controller that was just opened is sized before its first frame
+		match mode {
+			Mode::Play(ref mut controller) => {
+				controller.fit_to_window(window_size);
+				controller.draw(&mut PistonBackend::new(&e, &assets), &assets.theme);
+			},
+			Mode::Edit(ref mut editor) => {
+				editor.fit_to_window(window_size);
+				editor.draw(&mut PistonBackend::new(&e, &assets), &assets.theme);
+			}
+		}
+	}
+
+	if let Mode::Play(controller) = mode {
+		session.game = controller.into_game();
+	}
+	session.theme = Some(themes[theme_index].name.clone());
+	session
+}
+
+// Plays a game of bughouse on two boards in one window until it is closed. F turns both boards
+// round and T changes the theme, bughouse games are not saved.
+pub fn run_bughouse_gui(theme: Option<&String>) {
+	let window = open_window();
+	let themes = load_themes();
+	let mut theme_index = find_theme(&themes, theme);
+
+	let mut assets = Assets::load(&window, themes[theme_index].clone());
+	let mut controller = BughouseController::new();
+	let mut window_size = (WINDOW_SIZE.0 as f64, WINDOW_SIZE.1 as f64);
+
+	for e in window {
+		if let Some(size) = e.resize_args() {
+			window_size = (size[0] as f64, size[1] as f64);
+		}
+		controller.fit_to_window(window_size);
+		controller.event(&e);
+
+		match e.press_args() {
+			Some(Button::Keyboard(Key::T)) => {
+				theme_index = (theme_index + 1) % themes.len();
+				assets.set_theme(&e, themes[theme_index].clone());
+			},
+			Some(Button::Keyboard(Key::F)) => { controller.flip_boards(); },
+			_ => {}
+		}
+
+		e.draw_2d(|_, g| {
+			clear([1.0, 1.0, 1.0, 1.0], g);
+		});
+
+		controller.draw(&mut PistonBackend::new(&e, &assets), &assets.theme);
+	}
+}
+
+fn open_window() -> PistonWindow {
+    let opengl = OpenGL::V3_2;
+    WindowSettings::new("piston: sprite", WINDOW_SIZE)
+        .exit_on_esc(true)
+        .opengl(opengl)
+        .into()
+}
+
+// Themes are cycled through with the T key
+fn load_themes() -> Vec<Theme> {
+	let mut themes = Theme::load_all(&resources_folder().join("themes"));
+	if themes.is_empty() {
+		themes.push(Theme::default());
+	}
+	themes
+}
+
+// The index of the theme with the given name, the first theme when there is no such theme
+fn find_theme(themes: &[Theme], name: Option<&String>) -> usize {
+	name.and_then(|name| themes.iter().position(|theme| &theme.name == name)).unwrap_or(0)
+}
@@ -0,0 +1,92 @@
+#![feature(append)]
+
+// Board, rules and notation for chess, with the Piston front end behind the "gui" feature and
+// headless PNG diagrams behind the "png" feature. Build with `default-features = false` to use the
+// rules without pulling in a windowing stack. The "serialization" feature adds serde support for the
+// board and game types, with JSON and binary helpers in `serialization`.
+
+extern crate rand;
+extern crate find_folder;
+
+#[cfg(feature = "gui")]
+extern crate piston_window;
+#[cfg(feature = "gui")]
+extern crate graphics;
+#[cfg(feature = "gui")]
+extern crate opengl_graphics;
+#[cfg(feature = "gui")]
+extern crate sprite;
+#[cfg(feature = "gui")]
+extern crate gfx_device_gl;
+#[cfg(feature = "gui")]
+extern crate piston;
+#[cfg(feature = "gui")]
+extern crate clipboard;
+#[cfg(feature = "png")]
+extern crate image;
+#[cfg(feature = "png")]
+extern crate rusttype;
+#[cfg(feature = "serialization")]
+extern crate serde;
+#[cfg(feature = "serialization")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "serialization")]
+extern crate serde_json;
+#[cfg(feature = "serialization")]
+extern crate bincode;
+
+pub mod board;
+pub mod chess960;
+pub mod clock;
+pub mod error;
+pub mod fairy;
+pub mod fen;
+pub mod game;
+pub mod notation;
+pub mod perft;
+pub mod pgn;
+pub mod search;
+pub mod session;
+pub mod svg;
+pub mod terminal;
+pub mod theme;
+pub mod uci;
+pub mod validate;
+pub mod variant;
+pub mod view;
+
+#[cfg(feature = "png")]
+pub mod diagram;
+#[cfg(feature = "png")]
+pub mod software_backend;
+
+#[cfg(feature = "serialization")]
+pub mod serialization;
+
+#[cfg(feature = "gui")]
+pub mod bughouse;
+#[cfg(feature = "gui")]
+pub mod controller;
+#[cfg(feature = "gui")]
+pub mod editor;
+#[cfg(feature = "gui")]
+pub mod gui;
+#[cfg(feature = "gui")]
+pub mod panel;
+#[cfg(feature = "gui")]
+pub mod piston_backend;
+#[cfg(feature = "gui")]
+pub mod promotion;
+
+pub use board::{BoardCoordinates, ChessBoard, ChessMove, ChessPiece, ChessPieceColor, ChessPieceType, Square};
+pub use clock::ChessClock;
+pub use error::ChessError;
+pub use fairy::PieceDefinition;
+pub use fen::STARTING_FEN;
+pub use game::{CastlingRight, ChessGame, DrawReason, GameResult, MoveRecord, WinReason};
+pub use pgn::{PgnGame, parse_pgn};
+pub use search::{SearchResult, search};
+pub use session::Session;
+pub use validate::PositionProblem;
+pub use variant::{Rules, Variant};
@@ -0,0 +1,185 @@
+use board::*;
+use error::ChessError;
+use game::ChessGame;
+
+// Letter used for a piece type in algebraic notation, pawns have none
+pub fn piece_letter(type_name: ChessPieceType) -> Option<char> {
+	match type_name {
+		ChessPieceType::Pawn => None,
+		ChessPieceType::Rook => Some('R'),
+		ChessPieceType::Knight => Some('N'),
+		ChessPieceType::Bishop => Some('B'),
+		ChessPieceType::Queen => Some('Q'),
+		ChessPieceType::King => Some('K'),
+		ChessPieceType::Fairy(letter) => Some(letter)
+	}
+}
+
+// The square named at the start of `text`, letters followed by digits as in 'e4' or 'j10', and the
+// text after it
+fn split_square_name(text: &str) -> Option<(BoardCoordinates, &str)> {
+	let rank_start = text.find(|c: char| !c.is_ascii_lowercase()).unwrap_or(text.len());
+	let rank_end = text[rank_start..].find(|c: char| !c.is_digit(10)).map_or(text.len(), |i| rank_start + i);
+	BoardCoordinates::new(&text[..rank_end]).ok().map(|coordinates| (coordinates, &text[rank_end..]))
+}
+
+fn get_drop_notation(type_name: ChessPieceType, coordinates: &BoardCoordinates) -> String {
+	format!("{}@{}", piece_letter(type_name).unwrap_or('P'), coordinates.to_string())
+}
+
+impl ChessGame {
+	// Standard algebraic notation for a move that is about to be played, without the check suffix
+	// since that depends on the position after the move. Drops are written with the piece letter,
+	// a pawn's too, e.g. 'N@f3' or 'P@e6'.
+	pub fn get_algebraic_notation(&self, chess_move: &ChessMove) -> String {
+		let board = self.get_board();
+		let start = chess_move.start_coordinates;
+		let end = chess_move.end_coordinates;
+
+		if let Some(type_name) = chess_move.drop {
+			return get_drop_notation(type_name, &end);
+		}
+
+		let piece = match board.get_contents_at_coordinates(&start) {
+			Ok(&Some(piece)) => piece,
+			_ => { return String::new(); }
+		};
+		if chess_move.castling_rook.is_some() {
+			return if chess_move.is_king_side_castling() { "O-O".to_string() } else { "O-O-O".to_string() };
+		}
+
+		// Pawns changing column always capture, even onto the empty square of an en passant capture
+		let is_capture = match board.get_contents_at_coordinates(&end) {
+			Ok(&Some(..)) => true,
+			_ => piece.get_type() == ChessPieceType::Pawn && start.col != end.col
+		};
+
+		let mut notation = String::new();
+		match piece_letter(piece.get_type()) {
+			Some(letter) => {
+				notation.push(letter);
+				notation.push_str(&self.get_disambiguation(&piece, chess_move));
+			},
+			None => {
+				// Pawn captures name the file the pawn came from
+				if is_capture {
+					notation.push_str(&start.get_file_name());
+				}
+			}
+		}
+
+		if is_capture {
+			notation.push('x');
+		}
+		notation.push_str(&end.to_string());
+
+		if let Some(promotion) = chess_move.promotion {
+			notation.push('=');
+			notation.push(piece_letter(promotion).unwrap_or('Q'));
+		}
+
+		notation
+	}
+
+	// The legal move meant by `text`, written either in algebraic notation ('Nf3', 'exd5', 'e8=Q+',
+	// 'N@f3' or '@e6' for drops) or as its start and end squares ('g1f3', 'e7e8q', 'e1h1' for
+	// castling with the h1 rook). Squares without a promotion piece promote to a queen.
+	pub fn parse_move(&self, text: &str) -> Result<ChessMove, ChessError> {
+		let text = text.trim().trim_right_matches(|c| c == '+' || c == '#' || c == '!' || c == '?');
+		let pawn_drop = format!("P{}", text);
+		let text = if text.starts_with('@') { &pawn_drop[..] } else { text };
+
+		let legal_moves = self.with_promotion_choices(self.get_all_legal_moves());
+
+		// Castling is sometimes written with zeros, which can otherwise be part of a rank such as 'a10'
+		let algebraic = if text.starts_with("0-0") { text.replace("0", "O") } else { text.replace("=", "") };
+		let matching = legal_moves.iter()
+			.find(|chess_move| self.get_algebraic_notation(chess_move).replace("=", "") == algebraic);
+		if let Some(chess_move) = matching {
+			return Ok(*chess_move);
+		}
+
+		let illegal = || ChessError::IllegalMove(text.to_string());
+		let squares = text.to_lowercase();
+		if !squares.is_ascii() {
+			return Err(illegal());
+		}
+
+		let (start, rest) = try!(split_square_name(&squares).ok_or_else(illegal));
+		let (end, rest) = try!(split_square_name(rest).ok_or_else(illegal));
+		if rest.len() > 1 {
+			return Err(illegal());
+		}
+		let promotion = match rest.chars().nth(0) {
+			Some(letter) => {
+				match self.get_rules().promotion_pieces.iter().find(|&&type_name| piece_letter(type_name).map_or(false, |l| l.to_ascii_lowercase() == letter)) {
+					Some(&type_name) => Some(type_name),
+					None => { return Err(illegal()); }
+				}
+			},
+			None => None
+		};
+
+		let legal_move = match try!(self.find_legal_move(&ChessMove::new(&start, &end)).map_err(|_| illegal())) {
+			Some(legal_move) => legal_move,
+			None => { return Err(illegal()); }
+		};
+		if !self.is_promotion_move(&legal_move) {
+			return if promotion.is_none() { Ok(legal_move) } else { Err(illegal()) };
+		}
+		// A pawn that has to promote becomes the first choice, usually a queen, when none is given
+		if promotion.is_none() && self.is_promotion_optional(&legal_move) {
+			return Ok(legal_move);
+		}
+		match promotion.or(self.get_rules().promotion_pieces.first().cloned()) {
+			Some(promotion) => Ok(legal_move.with_promotion(promotion)),
+			None => Err(illegal())
+		}
+	}
+
+	// The start and end squares of a move followed by any promotion piece, e.g. 'e7e8q', as used by
+	// UCI. Castling is written as the king's move, or as the king taking its own rook in Chess960.
+	// Drops are written as in algebraic notation.
+	pub fn get_coordinate_notation(&self, chess_move: &ChessMove) -> String {
+		if let Some(type_name) = chess_move.drop {
+			return get_drop_notation(type_name, &chess_move.end_coordinates);
+		}
+
+		let end = match chess_move.castling_rook {
+			Some(rook) if self.is_chess960() => rook,
+			_ => chess_move.end_coordinates
+		};
+		let mut notation = format!("{}{}", chess_move.start_coordinates.to_string(), end.to_string());
+		if let Some(promotion) = chess_move.promotion {
+			notation.extend(piece_letter(promotion).unwrap_or('Q').to_lowercase());
+		}
+
+		notation
+	}
+
+	// The file, rank or both of the moving piece when another piece of the same kind could reach
+	// the same square
+	fn get_disambiguation(&self, piece: &ChessPiece, chess_move: &ChessMove) -> String {
+		let board = self.get_board();
+		let start = chess_move.start_coordinates;
+
+		let rivals: Vec<BoardCoordinates> = board.get_all_coordinates().into_iter()
+			.filter(|coordinates| *coordinates != start)
+			.filter(|coordinates| board.get_contents_at_coordinates(coordinates) == Ok(&Some(*piece)))
+			.filter(|coordinates| {
+				self.get_legal_moves(coordinates).unwrap_or(Vec::new()).iter()
+					.any(|rival_move| rival_move.end_coordinates == chess_move.end_coordinates)
+			})
+			.collect();
+
+		if rivals.is_empty() {
+			String::new()
+		} else if rivals.iter().all(|rival| rival.col != start.col) {
+			start.get_file_name()
+		} else if rivals.iter().all(|rival| rival.row != start.row) {
+			start.get_rank_name()
+		} else {
+			start.to_string()
+		}
+	}
+}
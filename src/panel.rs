@@ -0,0 +1,185 @@
+use board::*;
+use game::ChessGame;
+use theme::Theme;
+use variant::WinCondition;
+use view::{Renderable, DrawBackend, Font, BOARD_ORIGIN, SQUARE_SIZE};
+
+pub const PANEL_WIDTH: f64 = 300.0;
+pub const PANEL_MARGIN: f64 = 40.0; // Between the board and the panel, and after the panel
+const TEXT_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+const MUTED_TEXT_COLOR: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+const CLOCK_BACKGROUND_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const ACTIVE_CLOCK_BACKGROUND_COLOR: [f32; 4] = [0.75, 0.87, 0.7, 1.0];
+const CURRENT_MOVE_COLOR: [f32; 4] = [0.98, 0.87, 0.5, 1.0];
+
+const CLOCK_HEIGHT: f64 = 44.0;
+const CAPTURED_PIECE_SCALE: f64 = 0.4;
+const POCKET_PIECE_SCALE: f64 = 0.5;
+const MOVE_LINE_HEIGHT: f64 = 22.0;
+const MOVE_LIST_LINES: usize = 14;
+
+// Move list, captured pieces, clocks and result, drawn to the right of the board
+pub struct SidePanel<'a> {
+	game: &'a ChessGame
+}
+
+impl<'a> SidePanel<'a> {
+	pub fn new(game: &'a ChessGame) -> SidePanel<'a> {
+		SidePanel {
+			game: game
+		}
+	}
+
+	fn get_origin(&self) -> (f64, f64) {
+		let board_width = self.game.get_board().get_width() as f64;
+		(BOARD_ORIGIN.0 + board_width * SQUARE_SIZE + PANEL_MARGIN, BOARD_ORIGIN.1)
+	}
+
+	// Where a player's clock is drawn, the player at the bottom of the board having theirs at the bottom
+	fn get_clock_y(&self, color: ChessPieceColor) -> f64 {
+		let (_, y) = self.get_origin();
+		if color == self.game.get_bottom_player_color() {
+			y + self.game.get_board().get_height() as f64 * SQUARE_SIZE - CLOCK_HEIGHT
+		} else {
+			y
+		}
+	}
+
+	// Each kind of piece in a player's pocket with how many there are and where it is drawn, in a
+	// row under the player's clock
+	pub fn get_pocket_layout(&self, color: ChessPieceColor) -> Vec<(ChessPieceType, usize, (f64, f64))> {
+		let (x, _) = self.get_origin();
+		let y = self.get_clock_y(color) + CLOCK_HEIGHT + 4.0;
+		let step = SQUARE_SIZE * POCKET_PIECE_SCALE * 1.5;
+
+		let mut layout: Vec<(ChessPieceType, usize, (f64, f64))> = Vec::new();
+		for &type_name in self.game.get_pocket(color) {
+			match layout.last_mut() {
+				Some(&mut (last_type, ref mut count, _)) if last_type == type_name => { *count += 1; },
+				_ => {
+					let position = (x + layout.len() as f64 * step, y);
+					layout.push((type_name, 1, position));
+				}
+			}
+		}
+
+		layout
+	}
+
+	// The pocketed piece under a window position, if any
+	pub fn pocket_piece_at_position(&self, position: (f64, f64)) -> Option<ChessPiece> {
+		let size = SQUARE_SIZE * POCKET_PIECE_SCALE;
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			for (type_name, _, (x, y)) in self.get_pocket_layout(color) {
+				if position.0 >= x && position.0 < x + size && position.1 >= y && position.1 < y + size {
+					return Some(ChessPiece::new(type_name, color));
+				}
+			}
+		}
+
+		None
+	}
+
+	// The pieces a player may drop, with a count beside each kind there is more than one of
+	fn draw_pocket(&self, backend: &mut DrawBackend, color: ChessPieceColor) {
+		let size = SQUARE_SIZE * POCKET_PIECE_SCALE;
+		for (type_name, count, (x, y)) in self.get_pocket_layout(color) {
+			backend.draw_sprite(&ChessPiece::new(type_name, color), (x, y), POCKET_PIECE_SCALE);
+			if count > 1 {
+				backend.draw_text(&count.to_string(), Font::Interface, 14, MUTED_TEXT_COLOR, (x + size, y + size));
+			}
+		}
+	}
+
+	// Clock and captured pieces for one player, or the pieces they hold when the rules have drops
+	fn draw_player(&self, backend: &mut DrawBackend, color: ChessPieceColor) {
+		let (x, _) = self.get_origin();
+		let y = self.get_clock_y(color);
+		let is_running = self.game.get_result().is_none() && self.game.get_side_to_move() == color;
+		let background = if is_running { ACTIVE_CLOCK_BACKGROUND_COLOR } else { CLOCK_BACKGROUND_COLOR };
+
+		backend.draw_rect([x, y, PANEL_WIDTH, CLOCK_HEIGHT], background);
+		backend.draw_text(&format!("{:?}", color), Font::Interface, 18, TEXT_COLOR, (x + 10.0, y + 29.0));
+		backend.draw_text(&self.game.get_clock(color).to_string(), Font::Interface, 26, TEXT_COLOR, (x + PANEL_WIDTH - 90.0, y + 32.0));
+
+		// Three-check counts down the checks still to give beside the name
+		if self.game.get_rules().win_conditions.contains(&WinCondition::ThreeCheck) {
+			let checks = match self.game.get_remaining_checks(color) {
+				1 => "1 check to win".to_string(),
+				remaining => format!("{} checks to win", remaining)
+			};
+			backend.draw_text(&checks, Font::Interface, 14, MUTED_TEXT_COLOR, (x + 80.0, y + 28.0));
+		}
+
+		if self.game.get_rules().drops {
+			self.draw_pocket(backend, color);
+			return;
+		}
+
+		let captured_y = y + CLOCK_HEIGHT + 4.0;
+		let captured_size = SQUARE_SIZE * CAPTURED_PIECE_SCALE;
+		let captured = self.game.get_captured_pieces(color);
+		for (i, piece) in captured.iter().enumerate() {
+			let piece_x = x + i as f64 * captured_size * 0.7;
+			backend.draw_sprite(piece, (piece_x, captured_y), CAPTURED_PIECE_SCALE);
+		}
+
+		let difference = self.game.get_material_difference(color);
+		if difference > 0 {
+			let text_x = x + captured.len() as f64 * captured_size * 0.7 + captured_size * 0.5;
+			backend.draw_text(&format!("+{}", difference), Font::Interface, 14, MUTED_TEXT_COLOR, (text_x, captured_y + 18.0));
+		}
+	}
+
+	// Moves are listed in pairs, 1. e4 e5, scrolling so the latest move stays visible
+	fn draw_move_list(&self, backend: &mut DrawBackend, top: f64) {
+		let (x, _) = self.get_origin();
+		let history = self.game.get_history();
+
+		// Games set up with black to move start the list with '1... e5'
+		let first_is_black = history.first()
+			.map_or(false, |record| record.piece.get_color() == ChessPieceColor::Black);
+		let offset = if first_is_black { 1 } else { 0 };
+
+		let line_count = (history.len() + offset + 1) / 2;
+		let first_line = if line_count > MOVE_LIST_LINES { line_count - MOVE_LIST_LINES } else { 0 };
+
+		for (i, record) in history.iter().enumerate() {
+			let index = i + offset;
+			let line = index / 2;
+			if line < first_line {
+				continue;
+			}
+
+			let y = top + (line - first_line) as f64 * MOVE_LINE_HEIGHT;
+			let move_x = x + 50.0 + (index % 2) as f64 * 110.0;
+
+			if index % 2 == 0 || i == 0 {
+				backend.draw_text(&format!("{}.", line + 1), Font::Interface, 16, MUTED_TEXT_COLOR, (x + 10.0, y + 16.0));
+			}
+
+			if i == history.len() - 1 {
+				backend.draw_rect([move_x - 4.0, y, 100.0, MOVE_LINE_HEIGHT], CURRENT_MOVE_COLOR);
+			}
+			backend.draw_text(&record.notation, Font::Interface, 16, TEXT_COLOR, (move_x, y + 16.0));
+		}
+	}
+}
+
+impl<'a> Renderable for SidePanel<'a> {
+	fn draw(&self, backend: &mut DrawBackend, _theme: &Theme) {
+		let (x, y) = self.get_origin();
+
+		self.draw_player(backend, ChessPieceColor::White);
+		self.draw_player(backend, ChessPieceColor::Black);
+
+		let move_list_top = y + CLOCK_HEIGHT + 40.0;
+		self.draw_move_list(backend, move_list_top);
+
+		if let Some(result) = self.game.get_result() {
+			let result_y = move_list_top + (MOVE_LIST_LINES as f64 + 1.0) * MOVE_LINE_HEIGHT;
+			backend.draw_text(result.get_score(), Font::Interface, 20, TEXT_COLOR, (x + 10.0, result_y));
+			backend.draw_text(&result.to_string(), Font::Interface, 16, MUTED_TEXT_COLOR, (x + 90.0, result_y));
+		}
+	}
+}
@@ -0,0 +1,44 @@
+use board::ChessMove;
+use game::ChessGame;
+
+impl ChessGame {
+	// Number of move sequences `depth` plies long from this position, counting every promotion
+	// piece separately. Comparing against known counts is how move generation bugs are found.
+	pub fn perft(&self, depth: u32) -> u64 {
+		if depth == 0 {
+			return 1;
+		}
+
+		let moves = self.with_promotion_choices(self.get_all_legal_moves());
+		if depth == 1 {
+			return moves.len() as u64;
+		}
+
+		moves.iter()
+			.map(|chess_move| {
+				let mut game = self.clone();
+				match game.apply_move(chess_move) {
+					Ok(_) => game.perft(depth - 1),
+					Err(_) => 0
+				}
+			})
+			.sum()
+	}
+
+	// The perft count below each legal move, for narrowing down which move a wrong count comes from
+	pub fn perft_divide(&self, depth: u32) -> Vec<(ChessMove, u64)> {
+		if depth == 0 {
+			return Vec::new();
+		}
+
+		self.with_promotion_choices(self.get_all_legal_moves()).into_iter()
+			.map(|chess_move| {
+				let mut game = self.clone();
+				match game.apply_move(&chess_move) {
+					Ok(_) => (chess_move, game.perft(depth - 1)),
+					Err(_) => (chess_move, 0)
+				}
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,194 @@
+use error::ChessError;
+use fen::STARTING_FEN;
+use game::ChessGame;
+use variant::{Variant, WinCondition};
+
+// The shipped antichess definition, built in so replaying a game does not depend on finding resources
+const ANTICHESS_DEFINITION: &'static str = include_str!("../resources/variants/antichess.variant");
+
+// One game read from a PGN file: its tag pairs and the moves as they were written
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+	pub tags: Vec<(String, String)>,
+	pub moves: Vec<String>,
+	pub result: Option<String> // The game termination marker, e.g. '1-0' or '*'
+}
+
+impl PgnGame {
+	fn new() -> PgnGame {
+		PgnGame {
+			tags: Vec::new(),
+			moves: Vec::new(),
+			result: None
+		}
+	}
+
+	pub fn get_tag(&self, name: &str) -> Option<&str> {
+		self.tags.iter()
+			.find(|&&(ref tag, _)| tag == name)
+			.map(|&(_, ref value)| &value[..])
+	}
+
+	// Whether the Variant tag names Chess960, which programs spell in several ways
+	pub fn is_chess960(&self) -> bool {
+		match self.get_tag("Variant").map(|variant| variant.to_lowercase().replace(" ", "")) {
+			Some(variant) => variant == "chess960" || variant == "fischerandom" || variant == "fischerrandom",
+			None => false
+		}
+	}
+
+	pub fn is_crazyhouse(&self) -> bool {
+		self.get_tag("Variant").map_or(false, |variant| variant.to_lowercase() == "crazyhouse")
+	}
+
+	// Whether the Variant tag names antichess, also known as losing chess or suicide chess
+	pub fn is_antichess(&self) -> bool {
+		match self.get_tag("Variant").map(|variant| variant.to_lowercase().replace(" ", "")) {
+			Some(variant) => variant == "antichess" || variant == "losingchess" || variant == "suicide",
+			None => false
+		}
+	}
+
+	// The win condition of a King of the Hill or Three-check Variant tag, written with or without spaces and dashes
+	pub fn get_variant_win_condition(&self) -> Option<WinCondition> {
+		match self.get_tag("Variant").map(|variant| variant.to_lowercase().replace(" ", "").replace("-", "")) {
+			Some(ref variant) if variant == "kingofthehill" => Some(WinCondition::KingOfTheHill),
+			Some(ref variant) if variant == "threecheck" => Some(WinCondition::ThreeCheck),
+			_ => None
+		}
+	}
+
+	// Plays through the moves from the starting position, or the one in the FEN tag, stopping at the
+	// first move that is not legal
+	pub fn replay(&self) -> Result<ChessGame, ChessError> {
+		let mut game = try!(ChessGame::from_fen(self.get_tag("FEN").unwrap_or(STARTING_FEN)));
+		if self.is_chess960() {
+			game.set_chess960(true);
+		}
+		if self.is_crazyhouse() {
+			let mut rules = game.get_rules().clone();
+			rules.drops = true;
+			game.set_rules(rules);
+		}
+		if self.is_antichess() {
+			let variant = try!(Variant::parse(ANTICHESS_DEFINITION).map_err(|e| ChessError::InvalidPgn(format!("Antichess rules: {}", e))));
+			game.set_rules(variant.rules);
+		}
+		if let Some(condition) = self.get_variant_win_condition() {
+			let mut rules = game.get_rules().clone();
+			if !rules.win_conditions.contains(&condition) {
+				rules.win_conditions.push(condition);
+			}
+			game.set_rules(rules);
+		}
+
+		for (i, text) in self.moves.iter().enumerate() {
+			let move_number = game.get_fullmove_number();
+			let color = game.get_side_to_move();
+			match game.parse_move(text).and_then(|chess_move| game.move_piece(&chess_move)) {
+				Ok(_) => {},
+				Err(e) => { return Err(ChessError::InvalidPgn(format!("Move {} ({:?}, ply {}): {}", move_number, color, i + 1, e))); }
+			}
+		}
+
+		Ok(game)
+	}
+}
+
+// Every game in the text of a PGN file. Comments, variations and annotation glyphs are skipped.
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, ChessError> {
+	let mut games: Vec<PgnGame> = Vec::new();
+	let mut game = PgnGame::new();
+	let mut chars = text.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'[' => {
+				// A tag after the moves of a game without a termination marker starts the next game
+				if !game.moves.is_empty() {
+					games.push(game);
+					game = PgnGame::new();
+				}
+
+				let tag: String = chars.by_ref().take_while(|&c| c != ']').collect();
+				game.tags.push(try!(parse_tag(&tag)));
+			},
+			'{' => {
+				if !chars.by_ref().any(|c| c == '}') {
+					return Err(ChessError::InvalidPgn("Unterminated comment".to_string()));
+				}
+			},
+			';' | '%' => {
+				chars.by_ref().any(|c| c == '\n');
+			},
+			'(' => {
+				let mut depth = 1;
+				while depth > 0 {
+					match chars.next() {
+						Some('(') => { depth += 1; },
+						Some(')') => { depth -= 1; },
+						Some(_) => {},
+						None => { return Err(ChessError::InvalidPgn("Unterminated variation".to_string())); }
+					}
+				}
+			},
+			c if c.is_whitespace() => {},
+			c => {
+				let mut token = c.to_string();
+				while let Some(&next) = chars.peek() {
+					if next.is_whitespace() || "[]{}();".contains(next) {
+						break;
+					}
+					token.push(next);
+					chars.next();
+				}
+
+				match &token[..] {
+					"1-0" | "0-1" | "1/2-1/2" | "*" => {
+						game.result = Some(token.clone());
+						games.push(game);
+						game = PgnGame::new();
+					},
+					_ if token.starts_with('$') => {},
+					_ => {
+						let text = strip_move_number(&token);
+						if !text.is_empty() {
+							game.moves.push(text.to_string());
+						}
+					}
+				}
+			}
+		}
+	}
+
+	if !game.moves.is_empty() || !game.tags.is_empty() {
+		games.push(game);
+	}
+
+	Ok(games)
+}
+
+// 'Name "Value"' from between the brackets of a tag pair
+fn parse_tag(tag: &str) -> Result<(String, String), ChessError> {
+	let tag = tag.trim();
+	let name: String = tag.chars().take_while(|c| !c.is_whitespace()).collect();
+	let rest = tag[name.len()..].trim();
+
+	if name.is_empty() || !rest.starts_with('"') || !rest.ends_with('"') || rest.len() < 2 {
+		return Err(ChessError::InvalidPgn(format!("Invalid tag pair [{}]", tag)));
+	}
+
+	let value = rest[1..rest.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\");
+	Ok((name, value))
+}
+
+// '12.e4' and '12...e5' become 'e4' and 'e5', a lone '12.' becomes empty. Castling written with
+// zeros is left alone since no dot follows the digits.
+fn strip_move_number(token: &str) -> &str {
+	let digits = token.chars().take_while(|c| c.is_digit(10)).count();
+	if digits > 0 && token[digits..].starts_with('.') {
+		token[digits..].trim_left_matches('.')
+	} else {
+		token
+	}
+}
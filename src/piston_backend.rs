@@ -0,0 +1,150 @@
+use board::{ChessPiece, ChessPieceColor, PIECE_TYPES};
+use gfx_device_gl::Resources;
+use piston_window::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use theme::Theme;
+use view::{DrawBackend, Font, SQUARE_SIZE, arrow_polygons, draw_fallback_piece, resources_folder, sprite_file_name};
+
+// Everything loaded from the resources folder that drawing in the window needs
+pub struct Assets {
+	pub theme: Theme,
+	pub sprites: PieceSprites,
+	glyphs: RefCell<Glyphs>,
+	coordinate_glyphs: RefCell<Glyphs>
+}
+
+impl Assets {
+	pub fn load(window: &PistonWindow, theme: Theme) -> Assets {
+		Assets {
+			sprites: PieceSprites::load(window, &resources_folder().join(&theme.piece_set)),
+			glyphs: RefCell::new(load_glyphs(window, "DejaVuSans.ttf")),
+			coordinate_glyphs: RefCell::new(load_glyphs(window, &theme.coordinate_font)),
+			theme: theme
+		}
+	}
+
+	// Swaps in the piece set, fonts and colors of another theme while the window stays open
+	pub fn set_theme(&mut self, window: &PistonWindow, theme: Theme) {
+		if theme.piece_set != self.theme.piece_set {
+			self.sprites = PieceSprites::load(window, &resources_folder().join(&theme.piece_set));
+		}
+		if theme.coordinate_font != self.theme.coordinate_font {
+			self.coordinate_glyphs = RefCell::new(load_glyphs(window, &theme.coordinate_font));
+		}
+		self.theme = theme;
+	}
+}
+
+fn load_glyphs(window: &PistonWindow, font: &str) -> Glyphs {
+	let path = resources_folder().join("fonts").join(font);
+	Glyphs::new(&path, window.factory.borrow().clone()).unwrap()
+}
+
+// The textures of every piece, loaded once from a piece set folder
+pub struct PieceSprites {
+	textures: HashMap<ChessPiece, Rc<Texture<Resources>>>
+}
+
+impl PieceSprites {
+	pub fn load(window: &PistonWindow, folder: &Path) -> PieceSprites {
+		let mut textures = HashMap::new();
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			for &type_name in &PIECE_TYPES {
+				let piece = ChessPiece::new(type_name, color);
+				let texture = Texture::from_path(
+					&mut *window.factory.borrow_mut(),
+					folder.join(sprite_file_name(&piece)),
+					Flip::None,
+					&TextureSettings::new()
+				).unwrap();
+				textures.insert(piece, Rc::new(texture));
+			}
+		}
+
+		PieceSprites {
+			textures: textures
+		}
+	}
+
+	pub fn get(&self, piece: &ChessPiece) -> Option<&Rc<Texture<Resources>>> {
+		self.textures.get(piece)
+	}
+}
+
+// Draws straight into a Piston window for the current render event
+pub struct PistonBackend<'a> {
+	window: &'a PistonWindow,
+	assets: &'a Assets
+}
+
+impl<'a> PistonBackend<'a> {
+	pub fn new(window: &'a PistonWindow, assets: &'a Assets) -> PistonBackend<'a> {
+		PistonBackend {
+			window: window,
+			assets: assets
+		}
+	}
+}
+
+impl<'a> DrawBackend for PistonBackend<'a> {
+	fn get_size(&self) -> (f64, f64) {
+		let mut size = (0.0, 0.0);
+		self.window.draw_2d(|c, _| {
+			let view_size = c.get_view_size();
+			size = (view_size[0], view_size[1]);
+		});
+		size
+	}
+
+	fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		self.window.draw_2d(|c, g| {
+			rectangle(color, rect, c.transform, g);
+		});
+	}
+
+	fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		self.window.draw_2d(|c, g| {
+			ellipse(color, rect, c.transform, g);
+		});
+	}
+
+	fn draw_sprite(&mut self, piece: &ChessPiece, position: (f64, f64), scale: f64) {
+		if let Some(texture) = self.assets.sprites.get(piece) {
+			// Sprites from other piece sets may not be exactly one square in size
+			let (width, _) = texture.get_size();
+			let scale = scale * SQUARE_SIZE / width as f64;
+			self.window.draw_2d(|c, g| {
+				image(&**texture, c.transform.trans(position.0, position.1).scale(scale, scale), g);
+			});
+		} else {
+			draw_fallback_piece(self, piece, position, scale);
+		}
+	}
+
+	fn draw_text(&mut self, text: &str, font: Font, font_size: u32, color: [f32; 4], position: (f64, f64)) {
+		let mut glyphs = match font {
+			Font::Interface => self.assets.glyphs.borrow_mut(),
+			Font::Coordinates => self.assets.coordinate_glyphs.borrow_mut()
+		};
+		self.window.draw_2d(|c, g| {
+			text::Text::new_color(color, font_size).draw(
+				text,
+				&mut *glyphs,
+				&c.draw_state,
+				c.transform.trans(position.0, position.1),
+				g
+			);
+		});
+	}
+
+	fn draw_arrow(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [f32; 4]) {
+		let (shaft, head) = arrow_polygons(from, to, width);
+		self.window.draw_2d(|c, g| {
+			polygon(color, &shaft, c.transform, g);
+			polygon(color, &head, c.transform, g);
+		});
+	}
+}
@@ -0,0 +1,74 @@
+use board::*;
+use theme::Theme;
+use view::{Renderable, DrawBackend, BoardLayout, SQUARE_SIZE};
+
+const OVERLAY_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.4];
+const CHOICE_BACKGROUND_COLOR: [f32; 4] = [0.95, 0.95, 0.95, 1.0];
+
+// Overlay shown when a pawn is dropped on a promotion rank. It lists the pieces the pawn can
+// become in a column starting at the promotion square and running towards the centre of the board.
+#[derive(Debug)]
+pub struct PromotionPicker {
+	chess_move: ChessMove,
+	color: ChessPieceColor,
+	choices: Vec<ChessPieceType>, // A pawn among them stands for not promoting, where that is allowed
+	layout: BoardLayout // Of the board the pawn is promoting on
+}
+
+impl PromotionPicker {
+	pub fn new(chess_move: ChessMove, color: ChessPieceColor, choices: Vec<ChessPieceType>, layout: BoardLayout) -> PromotionPicker {
+		PromotionPicker {
+			chess_move: chess_move,
+			color: color,
+			choices: choices,
+			layout: layout
+		}
+	}
+
+	// The move that is waiting for a promotion piece to be picked
+	pub fn get_move(&self) -> &ChessMove {
+		&self.chess_move
+	}
+
+	// Window position of the top left corner of each choice, in the order they were given
+	fn choice_positions(&self) -> Vec<(f64, f64)> {
+		let (x, y) = self.layout.square_position(&self.chess_move.end_coordinates);
+
+		// Promotion squares in the top half of the window list their choices downwards
+		let board_middle = self.layout.origin.1 + self.layout.get_board_size().1 / 2.0;
+		let step = if y < board_middle {
+			SQUARE_SIZE
+		} else {
+			-SQUARE_SIZE
+		};
+
+		(0..self.choices.len())
+			.map(|i| (x, y + step * i as f64))
+			.collect()
+	}
+
+	// The piece type under a window position, if the position is on one of the choices
+	pub fn choice_at_position(&self, position: (f64, f64)) -> Option<ChessPieceType> {
+		for (i, &(x, y)) in self.choice_positions().iter().enumerate() {
+			if position.0 >= x && position.0 < x + SQUARE_SIZE &&
+				position.1 >= y && position.1 < y + SQUARE_SIZE
+			{
+				return Some(self.choices[i]);
+			}
+		}
+
+		None
+	}
+}
+
+impl Renderable for PromotionPicker {
+	fn draw(&self, backend: &mut DrawBackend, _theme: &Theme) {
+		let (width, height) = backend.get_size();
+		backend.draw_rect([0.0, 0.0, width, height], OVERLAY_COLOR);
+
+		for (i, &(x, y)) in self.choice_positions().iter().enumerate() {
+			backend.draw_rect([x, y, SQUARE_SIZE, SQUARE_SIZE], CHOICE_BACKGROUND_COLOR);
+			backend.draw_sprite(&ChessPiece::new(self.choices[i], self.color), (x, y), 1.0);
+		}
+	}
+}
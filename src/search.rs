@@ -0,0 +1,204 @@
+use board::*;
+use game::{ChessGame, GameResult};
+
+// Score of being checkmated at the root, mates further away score a little less
+const MATE_SCORE: i32 = 100000;
+
+// What a search found from a position
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+	pub best_move: Option<ChessMove>,
+	pub score: i32, // In centipawns, from the point of view of the side to move
+	pub principal_variation: Vec<ChessMove>, // The line of best play found, starting with best_move
+	pub nodes: u64
+}
+
+impl SearchResult {
+	// Moves until mate when the score is a forced mate, negative when the side to move is being mated
+	pub fn get_mate_in(&self) -> Option<i32> {
+		if self.score.abs() < MATE_SCORE - 1000 {
+			return None;
+		}
+
+		let plies = MATE_SCORE - self.score.abs();
+		let moves = (plies + 1) / 2;
+		Some(if self.score > 0 { moves } else { -moves })
+	}
+}
+
+// Alpha-beta search of every line `depth` plies deep, followed by captures until the position is
+// quiet, scoring the positions at the end by material and piece placement
+pub fn search(game: &ChessGame, depth: u32) -> SearchResult {
+	let mut nodes = 0u64;
+	let (score, principal_variation) = negamax(game, depth, 0, -MATE_SCORE - 1, MATE_SCORE + 1, &mut nodes);
+
+	SearchResult {
+		best_move: principal_variation.first().cloned(),
+		score: score,
+		principal_variation: principal_variation,
+		nodes: nodes
+	}
+}
+
+fn negamax(game: &ChessGame, depth: u32, ply: i32, mut alpha: i32, beta: i32, nodes: &mut u64) -> (i32, Vec<ChessMove>) {
+	*nodes += 1;
+
+	// The rules may end the game with moves still left, e.g. a king reaching the centre
+	if let Some(result) = game.get_variant_result() {
+		return (get_result_score(game, result, ply), Vec::new());
+	}
+
+	let moves = game.with_promotion_choices(game.get_all_legal_moves());
+	if moves.is_empty() {
+		// How the game ends without moves is up to the rules, stalemate may even win
+		let score = game.get_position_result().map_or(0, |result| get_result_score(game, result, ply));
+		return (score, Vec::new());
+	}
+	if game.get_halfmove_clock() >= 100 {
+		return (0, Vec::new());
+	}
+	if depth == 0 {
+		return (quiescence(game, alpha, beta, nodes), Vec::new());
+	}
+
+	let mut best_line: Vec<ChessMove> = Vec::new();
+	for chess_move in order_moves(game, moves) {
+		let mut next = game.clone();
+		if next.apply_move(&chess_move).is_err() {
+			continue;
+		}
+		let (score, line) = negamax(&next, depth - 1, ply + 1, -beta, -alpha, nodes);
+		let score = -score;
+
+		if score > alpha || best_line.is_empty() {
+			best_line = vec![chess_move];
+			best_line.extend(line);
+		}
+		if score > alpha {
+			alpha = score;
+			if alpha >= beta {
+				break;
+			}
+		}
+	}
+
+	(alpha, best_line)
+}
+
+// A finished game scored for the side to move, sooner wins scoring higher
+fn get_result_score(game: &ChessGame, result: GameResult, ply: i32) -> i32 {
+	match result {
+		GameResult::Win(winner, _) if winner == game.get_side_to_move() => MATE_SCORE - ply,
+		GameResult::Win(..) => -MATE_SCORE + ply,
+		GameResult::Draw(_) => 0
+	}
+}
+
+// Plays out captures and promotions until the position is quiet, so a search does not stop in the
+// middle of an exchange and count a piece as won when it is about to be taken back
+fn quiescence(game: &ChessGame, mut alpha: i32, beta: i32, nodes: &mut u64) -> i32 {
+	*nodes += 1;
+
+	if let Some(result) = game.get_variant_result() {
+		return get_result_score(game, result, 0);
+	}
+
+	// The side to move can usually do at least as well as standing still
+	let standing = evaluate(game);
+	if standing >= beta {
+		return standing;
+	}
+	if standing > alpha {
+		alpha = standing;
+	}
+
+	let board = game.get_board();
+	let captures: Vec<ChessMove> = game.with_promotion_choices(game.get_all_legal_moves()).into_iter()
+		.filter(|chess_move| {
+			chess_move.promotion.is_some() ||
+				board.get_contents_at_coordinates(&chess_move.end_coordinates) != Ok(&None)
+		})
+		.collect();
+
+	for chess_move in order_moves(game, captures) {
+		let mut next = game.clone();
+		if next.apply_move(&chess_move).is_err() {
+			continue;
+		}
+		let score = -quiescence(&next, -beta, -alpha, nodes);
+		if score > alpha {
+			alpha = score;
+			if alpha >= beta {
+				break;
+			}
+		}
+	}
+
+	alpha
+}
+
+// Captures of the most valuable pieces and promotions first, so good moves cut off the rest sooner
+fn order_moves(game: &ChessGame, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+	let board = game.get_board();
+	let mut scored: Vec<(i32, ChessMove)> = moves.into_iter()
+		.map(|chess_move| {
+			let mut priority = 0i32;
+			if let Ok(&Some(victim)) = board.get_contents_at_coordinates(&chess_move.end_coordinates) {
+				priority += game.get_piece_value(victim.get_type()) as i32 * 10;
+				if let Ok(&Some(attacker)) = board.get_contents_at_coordinates(&chess_move.start_coordinates) {
+					priority -= game.get_piece_value(attacker.get_type()) as i32;
+				}
+			}
+			if let Some(promotion) = chess_move.promotion {
+				priority += game.get_piece_value(promotion) as i32 * 10;
+			}
+			(priority, chess_move)
+		})
+		.collect();
+
+	scored.sort_by(|a, b| b.0.cmp(&a.0));
+	scored.into_iter().map(|(_, chess_move)| chess_move).collect()
+}
+
+// Static score of a position for the side to move: material, plus small bonuses for advanced pawns
+// and for minor pieces near the centre
+pub fn evaluate(game: &ChessGame) -> i32 {
+	let board = game.get_board();
+	let (width, height) = board.get_dimensions();
+	let side_to_move = game.get_side_to_move();
+
+	let mut score = 0i32;
+	for coordinates in board.get_all_coordinates() {
+		let piece = match board.get_contents_at_coordinates(&coordinates) {
+			Ok(&Some(piece)) => piece,
+			_ => { continue; }
+		};
+
+		let mut value = game.get_piece_value(piece.get_type()) as i32 * 100;
+		match piece.get_type() {
+			ChessPieceType::Pawn => {
+				let home_row = game.get_home_row(piece.get_color()) as i32;
+				value += (coordinates.row as i32 - home_row).abs() * 5;
+			},
+			ChessPieceType::Knight | ChessPieceType::Bishop => {
+				// Twice the distance from the centre, so it stays whole on even sized boards
+				let col_distance = (2 * coordinates.col as i32 - (width as i32 - 1)).abs();
+				let row_distance = (2 * coordinates.row as i32 - (height as i32 - 1)).abs();
+				value += 20 - 2 * (col_distance + row_distance);
+			},
+			_ => {}
+		}
+
+		score += if piece.get_color() == side_to_move { value } else { -value };
+	}
+
+	// Pieces in hand are worth as much as on the board
+	for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+		for &type_name in game.get_pocket(color) {
+			let value = game.get_piece_value(type_name) as i32 * 100;
+			score += if color == side_to_move { value } else { -value };
+		}
+	}
+
+	score
+}
@@ -0,0 +1,75 @@
+use bincode;
+use board::{BoardCoordinates, ChessPiece, ChessPieceColor, ChessPieceType};
+use clock::ChessClock;
+use error::ChessError;
+use fairy::PieceDefinition;
+use game::{CastlingRight, GameResult, MoveRecord};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+use variant::Rules;
+
+// Version of the layout below. Bump it whenever a saved field is added, removed or changes meaning,
+// so readers refuse data of any other version instead of misreading it. Every field is required,
+// the binary form has no way of telling a missing field from the next one.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// How a board is written out: its width, height and the occupied squares. Reading one back goes
+// through the board's bounds checks, so saved data can not put a piece off the board.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBoard<T> {
+	pub width: u8,
+	pub height: u8,
+	pub pieces: Vec<(BoardCoordinates, T)>
+}
+
+// How a game is written out, including its history and both clocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+	pub version: u32,
+	pub board: SavedBoard<ChessPiece>,
+	pub bottom_color: ChessPieceColor, // Whose side is drawn at the bottom, row 0 is white's either way
+	pub side_to_move: ChessPieceColor,
+	pub history: Vec<MoveRecord>,
+	pub white_clock: ChessClock,
+	pub black_clock: ChessClock,
+	pub result: Option<GameResult>,
+	pub castling_rights: Vec<CastlingRight>,
+	pub en_passant: Option<BoardCoordinates>,
+	pub halfmove_clock: u32,
+	pub fullmove_number: u32,
+	pub chess960: bool,
+	pub pieces: Vec<PieceDefinition>, // Fairy pieces the game uses
+	pub rules: Rules,
+	pub white_pocket: Vec<ChessPieceType>,
+	pub black_pocket: Vec<ChessPieceType>,
+	pub promoted: Vec<BoardCoordinates>, // Squares holding promoted pieces, which go back to a pocket as pawns
+	pub white_checks: u8, // Checks given, for Three-check
+	pub black_checks: u8
+}
+
+// How a fairy piece is written out. Its moves are read back from the Betza notation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPiece {
+	pub name: String,
+	pub letter: char,
+	pub betza: String,
+	pub value: u32
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, ChessError> {
+	serde_json::to_string_pretty(value).map_err(|e| ChessError::InvalidData(e.to_string()))
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, ChessError> {
+	serde_json::from_str(json).map_err(|e| ChessError::InvalidData(e.to_string()))
+}
+
+// The compact binary form, for sending over the network or storing many games
+pub fn to_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, ChessError> {
+	bincode::serialize(value).map_err(|e| ChessError::InvalidData(e.to_string()))
+}
+
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ChessError> {
+	bincode::deserialize(bytes).map_err(|e| ChessError::InvalidData(e.to_string()))
+}
@@ -0,0 +1,121 @@
+use error::ChessError;
+use game::ChessGame;
+#[cfg(feature = "serialization")]
+use serialization::{SCHEMA_VERSION, from_json, to_json};
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+use std::env;
+#[cfg(feature = "serialization")]
+use std::fs::{self, File};
+#[cfg(feature = "serialization")]
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+// Everything needed to pick a game back up where it was left: the game itself (position, history,
+// clocks and which side is at the bottom of the board) along with the players and display settings
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialization", serde(into = "SavedSession", try_from = "SavedSession"))]
+pub struct Session {
+	pub game: ChessGame,
+	pub white_name: String,
+	pub black_name: String,
+	pub theme: Option<String>, // Name of the window's theme, the first one found when not set
+	pub ascii: bool // Whether the terminal draws pieces as letters
+}
+
+// How a session is written out, see `serialization` for how the game inside it is
+#[cfg(feature = "serialization")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+	pub version: u32,
+	pub game: ChessGame,
+	pub white_name: String,
+	pub black_name: String,
+	pub theme: Option<String>,
+	pub ascii: bool
+}
+
+#[cfg(feature = "serialization")]
+impl From<Session> for SavedSession {
+	fn from(session: Session) -> SavedSession {
+		SavedSession {
+			version: SCHEMA_VERSION,
+			game: session.game,
+			white_name: session.white_name,
+			black_name: session.black_name,
+			theme: session.theme,
+			ascii: session.ascii
+		}
+	}
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<SavedSession> for Session {
+	type Error = ChessError;
+
+	fn try_from(saved: SavedSession) -> Result<Session, ChessError> {
+		if saved.version != SCHEMA_VERSION {
+			return Err(ChessError::UnsupportedVersion(saved.version));
+		}
+
+		Ok(Session {
+			game: saved.game,
+			white_name: saved.white_name,
+			black_name: saved.black_name,
+			theme: saved.theme,
+			ascii: saved.ascii
+		})
+	}
+}
+
+impl Session {
+	pub fn new(game: ChessGame) -> Session {
+		Session {
+			game: game,
+			white_name: "White".to_string(),
+			black_name: "Black".to_string(),
+			theme: None,
+			ascii: false
+		}
+	}
+
+	// Writes the session as JSON, creating the folder it goes in if needed
+	#[cfg(feature = "serialization")]
+	pub fn save(&self, path: &Path) -> Result<(), ChessError> {
+		let json = try!(to_json(self));
+		if let Some(folder) = path.parent() {
+			try!(fs::create_dir_all(folder).map_err(|e| ChessError::Io(e.to_string())));
+		}
+
+		File::create(path)
+			.and_then(|mut file| file.write_all(json.as_bytes()))
+			.map_err(|e| ChessError::Io(format!("Could not write {}: {}", path.display(), e)))
+	}
+
+	#[cfg(feature = "serialization")]
+	pub fn load(path: &Path) -> Result<Session, ChessError> {
+		let mut json = String::new();
+		try!(File::open(path)
+			.and_then(|mut file| file.read_to_string(&mut json))
+			.map_err(|e| ChessError::Io(format!("Could not read {}: {}", path.display(), e))));
+		from_json(&json)
+	}
+
+	#[cfg(not(feature = "serialization"))]
+	pub fn save(&self, _path: &Path) -> Result<(), ChessError> {
+		Err(ChessError::Io("Saving needs the \"serialization\" feature".to_string()))
+	}
+
+	#[cfg(not(feature = "serialization"))]
+	pub fn load(_path: &Path) -> Result<Session, ChessError> {
+		Err(ChessError::Io("Loading needs the \"serialization\" feature".to_string()))
+	}
+}
+
+// Where the session is saved when a game is closed, and read from to continue the last game
+pub fn autosave_path() -> Option<PathBuf> {
+	env::var_os("HOME")
+		.or_else(|| env::var_os("USERPROFILE"))
+		.map(|home| PathBuf::from(home).join(".rust_chess").join("last_game.json"))
+}
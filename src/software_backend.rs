@@ -0,0 +1,301 @@
+use board::{ChessPiece, ChessPieceColor, PIECE_TYPES};
+use image::{self, RgbaImage};
+use rusttype::{self, FontCollection, Scale, point};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use theme::Theme;
+use view::{DrawBackend, Font, SQUARE_SIZE, arrow_polygons, draw_fallback_piece, sprite_file_name};
+
+// Draws into an rgba pixel buffer in memory, so boards can be rendered without a window or GPU.
+//
+// Drawing calls use the same window coordinates as the Piston backend, mapped onto the buffer by
+// a scale and offset so the layout code does not need to know the size of the output.
+pub struct SoftwareBackend {
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>,
+	scale: f64,
+	offset: (f64, f64),
+	sprites: HashMap<ChessPiece, RgbaImage>,
+	interface_font: rusttype::Font<'static>,
+	coordinate_font: rusttype::Font<'static>
+}
+
+#[allow(dead_code)]
+impl SoftwareBackend {
+	// A transparent buffer with the sprites and fonts of `theme` loaded from the resources folder
+	pub fn new(width: u32, height: u32, theme: &Theme, resources: &Path) -> Result<SoftwareBackend, String> {
+		let mut sprites = HashMap::new();
+		let piece_set = resources.join(&theme.piece_set);
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			for &type_name in &PIECE_TYPES {
+				let piece = ChessPiece::new(type_name, color);
+				let path = piece_set.join(sprite_file_name(&piece));
+				let sprite = match image::open(&path) {
+					Ok(sprite) => sprite.to_rgba(),
+					Err(e) => { return Err(format!("Could not load sprite {}: {:?}", path.display(), e)); }
+				};
+				sprites.insert(piece, sprite);
+			}
+		}
+
+		Ok(SoftwareBackend {
+			width: width,
+			height: height,
+			pixels: vec![0; (width * height * 4) as usize],
+			scale: 1.0,
+			offset: (0.0, 0.0),
+			sprites: sprites,
+			interface_font: try!(load_font(&resources.join("fonts").join("DejaVuSans.ttf"))),
+			coordinate_font: try!(load_font(&resources.join("fonts").join(&theme.coordinate_font)))
+		})
+	}
+
+	// Drawing coordinates are multiplied by `scale` and then shifted by `offset` pixels
+	pub fn set_transform(&mut self, scale: f64, offset: (f64, f64)) {
+		self.scale = scale;
+		self.offset = offset;
+	}
+
+	pub fn clear(&mut self, color: [f32; 4]) {
+		let rgba = to_rgba(color);
+		for pixel in self.pixels.chunks_mut(4) {
+			for i in 0..4 {
+				pixel[i] = rgba[i];
+			}
+		}
+	}
+
+	pub fn get_width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn get_height(&self) -> u32 {
+		self.height
+	}
+
+	// Rows of rgba pixels from the top of the buffer down
+	pub fn get_pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+
+	pub fn into_image(self) -> RgbaImage {
+		RgbaImage::from_raw(self.width, self.height, self.pixels).unwrap()
+	}
+
+	fn transform(&self, position: (f64, f64)) -> (f64, f64) {
+		(position.0 * self.scale + self.offset.0, position.1 * self.scale + self.offset.1)
+	}
+
+	// Blends `color` over a pixel with the given coverage, ignoring pixels outside the buffer
+	fn blend(&mut self, x: i64, y: i64, color: [f32; 4], coverage: f32) {
+		if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+			return;
+		}
+
+		let index = ((y as u32 * self.width + x as u32) * 4) as usize;
+		let alpha = color[3] * coverage;
+		let destination_alpha = self.pixels[index + 3] as f32 / 255.0;
+		let out_alpha = alpha + destination_alpha * (1.0 - alpha);
+		if out_alpha <= 0.0 {
+			return;
+		}
+
+		for i in 0..3 {
+			let destination = self.pixels[index + i] as f32 / 255.0;
+			let value = (color[i] * alpha + destination * destination_alpha * (1.0 - alpha)) / out_alpha;
+			self.pixels[index + i] = (value * 255.0).round() as u8;
+		}
+		self.pixels[index + 3] = (out_alpha * 255.0).round() as u8;
+	}
+
+	// Runs `inside` on the centre of every pixel in the bounding box and fills those it accepts
+	fn fill<F: Fn(f64, f64) -> bool>(&mut self, bounds: [f64; 4], color: [f32; 4], inside: F) {
+		let min_x = bounds[0].floor().max(0.0) as i64;
+		let min_y = bounds[1].floor().max(0.0) as i64;
+		let max_x = (bounds[0] + bounds[2]).ceil().min(self.width as f64) as i64;
+		let max_y = (bounds[1] + bounds[3]).ceil().min(self.height as f64) as i64;
+
+		for y in min_y..max_y {
+			for x in min_x..max_x {
+				if inside(x as f64 + 0.5, y as f64 + 0.5) {
+					self.blend(x, y, color, 1.0);
+				}
+			}
+		}
+	}
+
+	fn fill_polygon(&mut self, points: &[[f64; 2]], color: [f32; 4]) {
+		let points: Vec<[f64; 2]> = points.iter()
+			.map(|point| {
+				let (x, y) = self.transform((point[0], point[1]));
+				[x, y]
+			})
+			.collect();
+
+		let min_x = points.iter().fold(::std::f64::MAX, |min, point| min.min(point[0]));
+		let min_y = points.iter().fold(::std::f64::MAX, |min, point| min.min(point[1]));
+		let max_x = points.iter().fold(::std::f64::MIN, |max, point| max.max(point[0]));
+		let max_y = points.iter().fold(::std::f64::MIN, |max, point| max.max(point[1]));
+
+		self.fill([min_x, min_y, max_x - min_x, max_y - min_y], color, |x, y| point_in_polygon(&points, x, y));
+	}
+}
+
+impl DrawBackend for SoftwareBackend {
+	fn get_size(&self) -> (f64, f64) {
+		(
+			(self.width as f64 - self.offset.0) / self.scale,
+			(self.height as f64 - self.offset.1) / self.scale
+		)
+	}
+
+	fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		let (x, y) = self.transform((rect[0], rect[1]));
+		let (width, height) = (rect[2] * self.scale, rect[3] * self.scale);
+		self.fill([x, y, width, height], color, |_, _| true);
+	}
+
+	fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		let (x, y) = self.transform((rect[0], rect[1]));
+		let (radius_x, radius_y) = (rect[2] * self.scale / 2.0, rect[3] * self.scale / 2.0);
+		let (centre_x, centre_y) = (x + radius_x, y + radius_y);
+		self.fill([x, y, radius_x * 2.0, radius_y * 2.0], color, |px, py| {
+			let dx = (px - centre_x) / radius_x;
+			let dy = (py - centre_y) / radius_y;
+			dx * dx + dy * dy <= 1.0
+		});
+	}
+
+	fn draw_sprite(&mut self, piece: &ChessPiece, position: (f64, f64), scale: f64) {
+		let (x, y) = self.transform(position);
+		let size = SQUARE_SIZE * scale * self.scale;
+
+		// Sample the sprite bilinearly for every destination pixel it covers
+		let pixels: Vec<(i64, i64, [f32; 4])> = match self.sprites.get(piece) {
+			Some(sprite) => {
+				let (sprite_width, sprite_height) = sprite.dimensions();
+				let mut pixels = Vec::new();
+				for py in y.floor() as i64..(y + size).ceil() as i64 {
+					for px in x.floor() as i64..(x + size).ceil() as i64 {
+						let u = ((px as f64 + 0.5 - x) / size * sprite_width as f64 - 0.5).max(0.0);
+						let v = ((py as f64 + 0.5 - y) / size * sprite_height as f64 - 0.5).max(0.0);
+						pixels.push((px, py, sample_bilinear(sprite, u, v)));
+					}
+				}
+				pixels
+			},
+			None => {
+				draw_fallback_piece(self, piece, position, scale);
+				return;
+			}
+		};
+
+		for (px, py, color) in pixels {
+			self.blend(px, py, color, 1.0);
+		}
+	}
+
+	fn draw_text(&mut self, text: &str, font: Font, font_size: u32, color: [f32; 4], position: (f64, f64)) {
+		let (x, y) = self.transform(position);
+		let scale = Scale::uniform(font_size as f32 * self.scale as f32);
+
+		let mut coverage: Vec<(i64, i64, f32)> = Vec::new();
+		{
+			let font = match font {
+				Font::Interface => &self.interface_font,
+				Font::Coordinates => &self.coordinate_font
+			};
+			for glyph in font.layout(text, scale, point(x as f32, y as f32)) {
+				if let Some(bounds) = glyph.pixel_bounding_box() {
+					glyph.draw(|gx, gy, v| {
+						coverage.push((bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64, v));
+					});
+				}
+			}
+		}
+
+		for (px, py, v) in coverage {
+			self.blend(px, py, color, v);
+		}
+	}
+
+	fn draw_arrow(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [f32; 4]) {
+		let (shaft, head) = arrow_polygons(from, to, width);
+		self.fill_polygon(&shaft, color);
+		self.fill_polygon(&head, color);
+	}
+}
+
+fn load_font(path: &Path) -> Result<rusttype::Font<'static>, String> {
+	let mut bytes = Vec::new();
+	match File::open(path).and_then(|mut file| file.read_to_end(&mut bytes)) {
+		Ok(_) => {},
+		Err(e) => { return Err(format!("Could not read font {}: {}", path.display(), e)); }
+	}
+
+	match FontCollection::from_bytes(bytes).into_font() {
+		Some(font) => Ok(font),
+		None => Err(format!("{} is not a font rusttype can read", path.display()))
+	}
+}
+
+fn to_rgba(color: [f32; 4]) -> [u8; 4] {
+	[
+		(color[0] * 255.0).round() as u8,
+		(color[1] * 255.0).round() as u8,
+		(color[2] * 255.0).round() as u8,
+		(color[3] * 255.0).round() as u8
+	]
+}
+
+fn sample_bilinear(sprite: &RgbaImage, u: f64, v: f64) -> [f32; 4] {
+	let (width, height) = sprite.dimensions();
+	let x0 = (u.floor() as u32).min(width - 1);
+	let y0 = (v.floor() as u32).min(height - 1);
+	let x1 = (x0 + 1).min(width - 1);
+	let y1 = (y0 + 1).min(height - 1);
+	let (fx, fy) = ((u - u.floor()) as f32, (v - v.floor()) as f32);
+
+	let mut result = [0.0f32; 4];
+	let corners = [
+		(x0, y0, (1.0 - fx) * (1.0 - fy)),
+		(x1, y0, fx * (1.0 - fy)),
+		(x0, y1, (1.0 - fx) * fy),
+		(x1, y1, fx * fy)
+	];
+
+	// Weight colors by alpha so transparent edges do not bleed dark fringes
+	for &(x, y, weight) in &corners {
+		let pixel = sprite.get_pixel(x, y).data;
+		let alpha = pixel[3] as f32 / 255.0;
+		for i in 0..3 {
+			result[i] += pixel[i] as f32 / 255.0 * alpha * weight;
+		}
+		result[3] += alpha * weight;
+	}
+
+	if result[3] > 0.0 {
+		for i in 0..3 {
+			result[i] /= result[3];
+		}
+	}
+
+	result
+}
+
+fn point_in_polygon(points: &[[f64; 2]], x: f64, y: f64) -> bool {
+	let mut inside = false;
+	let mut j = points.len() - 1;
+	for i in 0..points.len() {
+		let (a, b) = (points[i], points[j]);
+		if (a[1] > y) != (b[1] > y) && x < (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]) + a[0] {
+			inside = !inside;
+		}
+		j = i;
+	}
+
+	inside
+}
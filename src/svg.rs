@@ -0,0 +1,245 @@
+use board::*;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use theme::Theme;
+use view::{BoardLayout, DrawBackend, Font, SQUARE_SIZE, arrow_polygons, draw_board_coordinates, draw_board_squares, draw_fallback_piece,
+	draw_square_arrow, highlight_square};
+
+const ARROW_COLOR: [f32; 4] = [0.08, 0.47, 0.13, 0.8];
+const MARK_COLOR: [f32; 4] = [0.8, 0.1, 0.1, 0.8];
+
+// Piece glyphs are drawn in a 45x45 box and scaled to the square size
+const GLYPH_SIZE: f64 = 45.0;
+
+const FONT_FAMILY: &'static str = "Helvetica, Arial, 'DejaVu Sans', sans-serif";
+
+// How an SVG diagram of a board is drawn
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+	pub square_size: f64,
+	pub flipped: bool, // Row 0 at the top instead of the bottom
+	pub coordinates: bool,
+	pub arrows: Vec<(BoardCoordinates, BoardCoordinates)>,
+	pub marks: Vec<BoardCoordinates>, // Squares circled on the diagram
+	pub highlights: Vec<BoardCoordinates>,
+	pub theme: Theme
+}
+
+impl Default for SvgOptions {
+	fn default() -> SvgOptions {
+		SvgOptions {
+			square_size: GLYPH_SIZE,
+			flipped: false,
+			coordinates: true,
+			arrows: Vec::new(),
+			marks: Vec::new(),
+			highlights: Vec::new(),
+			theme: Theme::default()
+		}
+	}
+}
+
+// A self-contained SVG of the position. Piece glyphs are defined once as vector paths and reused,
+// so the diagram needs no external images or fonts besides a generic one for the coordinates.
+pub fn board_to_svg(board: &ChessBoard<ChessPiece>, options: &SvgOptions) -> String {
+	let theme = &options.theme;
+	let margin = if options.coordinates { options.square_size / 2.0 } else { 0.0 };
+	let layout = BoardLayout::diagram(board.get_dimensions(), options.square_size, margin, options.flipped);
+	let (total_width, total_height) = layout.get_total_size();
+	let square_size = options.square_size;
+
+	let mut backend = SvgBackend::new((total_width, total_height));
+	backend.svg.push_str(&format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"1.1\" \
+		width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+		total_width, total_height
+	));
+
+	backend.svg.push_str("<defs>\n");
+	for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+		for &type_name in &PIECE_TYPES {
+			backend.svg.push_str(&glyph_definition(&ChessPiece::new(type_name, color)));
+		}
+	}
+	backend.svg.push_str("</defs>\n");
+
+	draw_board_squares(&mut backend, theme, &layout);
+	for coordinates in &options.highlights {
+		highlight_square(&mut backend, &layout, coordinates, theme.last_move_highlight);
+	}
+	if options.coordinates {
+		draw_board_coordinates(&mut backend, theme, &layout, (square_size / 3.5).round() as u32);
+	}
+	board.draw_pieces(&mut backend, &layout, None);
+
+	for coordinates in &options.marks {
+		let (x, y) = layout.square_centre(coordinates);
+		backend.svg.push_str(&format!(
+			"<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke-width=\"{}\" {}/>\n",
+			x, y, square_size * 0.45, square_size / 15.0, stroke_attributes(MARK_COLOR)
+		));
+	}
+
+	for &(from, to) in &options.arrows {
+		draw_square_arrow(&mut backend, &layout, &from, &to, ARROW_COLOR);
+	}
+
+	backend.svg.push_str("</svg>\n");
+	backend.svg
+}
+
+pub fn save_svg(board: &ChessBoard<ChessPiece>, options: &SvgOptions, path: &Path) -> Result<(), String> {
+	File::create(path)
+		.and_then(|mut file| file.write_all(board_to_svg(board, options).as_bytes()))
+		.map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn glyph_id(piece: &ChessPiece) -> String {
+	format!("{:?}-{:?}", piece.get_color(), piece.get_type()).to_lowercase()
+}
+
+// The outline and inner detail lines of each piece in the 45x45 glyph box. Fairy pieces have no
+// glyph and are drawn by `draw_fallback_piece` instead.
+fn glyph_paths(type_name: ChessPieceType) -> (&'static str, &'static str) {
+	let outline = match type_name {
+		ChessPieceType::Pawn => "M22.5,9a4.5,4.5 0 1 0 0.01,0z \
+			M16,33.5C16,26 19,21.5 20.5,18.5L24.5,18.5C26,21.5 29,26 29,33.5z",
+		ChessPieceType::Rook => "M14,16L14,10L17,10L17,12.5L20.5,12.5L20.5,10L24.5,10L24.5,12.5L28,12.5L28,10L31,10\
+			L31,16L29,18L29,31L31,33.5L14,33.5L16,31L16,18z",
+		ChessPieceType::Knight => "M15,33.5C15,27 18,24 21,21C18,21.5 15.5,23 13.5,24.5C11.5,25 10,23.5 10.5,21.5\
+			C12,18 16,14 19,11.5L19,8.5L21.5,10.5L23.5,8L24.5,11C30,12.5 33,18 33,25L33,33.5z",
+		ChessPieceType::Bishop => "M22.5,7a2.5,2.5 0 1 0 0.01,0z \
+			M22.5,11C17,14 15,20 16.5,25C17.5,28 19,29 19,29L26,29C26,29 27.5,28 28.5,25C30,20 28,14 22.5,11z \
+			M17,29L28,29L29,33.5L16,33.5z",
+		ChessPieceType::Queen => "M12,33.5L9.5,14L15.5,25L16,11.5L19.5,24L22.5,10L25.5,24L29,11.5L29.5,25L35.5,14L33,33.5z \
+			M9.5,12a2,2 0 1 0 0.01,0z M16,9.5a2,2 0 1 0 0.01,0z M22.5,8a2,2 0 1 0 0.01,0z \
+			M29,9.5a2,2 0 1 0 0.01,0z M35.5,12a2,2 0 1 0 0.01,0z",
+		ChessPieceType::King => "M21,6L24,6L24,9L27,9L27,12L24,12L24,15L21,15L21,12L18,12L18,9L21,9z \
+			M22.5,23C20,19.5 19.5,17 22.5,15C25.5,17 25,19.5 22.5,23z \
+			M13,33.5C11,27 10,21 15,19C18,18 21,20 22.5,23C24,20 27,18 30,19C35,21 34,27 32,33.5z",
+		ChessPieceType::Fairy(_) => ""
+	};
+	let details = match type_name {
+		ChessPieceType::Pawn => "",
+		ChessPieceType::Rook => "M16,18L29,18M16,31L29,31",
+		ChessPieceType::Knight => "M19.5,15a1,1 0 1 0 0.01,0z",
+		ChessPieceType::Bishop => "M22.5,16L22.5,24M19,20L26,20",
+		ChessPieceType::Queen => "M13,30L32,30",
+		ChessPieceType::King => "M14,29.5L31,29.5",
+		ChessPieceType::Fairy(_) => ""
+	};
+
+	(outline, details)
+}
+
+// Every piece stands on the same base
+const GLYPH_BASE: &'static str = "M10,39.5L35,39.5L35,36C35,34.5 34,33.5 32.5,33.5L12.5,33.5C11,33.5 10,34.5 10,36z";
+
+fn glyph_definition(piece: &ChessPiece) -> String {
+	let (outline, details) = glyph_paths(piece.get_type());
+	let (fill, detail) = match piece.get_color() {
+		ChessPieceColor::White => ("#ffffff", "#000000"),
+		ChessPieceColor::Black => ("#000000", "#ffffff")
+	};
+
+	let mut definition = format!(
+		"<g id=\"{}\" stroke=\"#000000\" stroke-width=\"1.5\" stroke-linejoin=\"round\">\
+		<path d=\"{} {}\" fill=\"{}\"/>",
+		glyph_id(piece), outline, GLYPH_BASE, fill
+	);
+	if !details.is_empty() {
+		definition.push_str(&format!("<path d=\"{}\" fill=\"none\" stroke=\"{}\"/>", details, detail));
+	}
+	definition.push_str("</g>\n");
+	definition
+}
+
+// Writes the drawing primitives as SVG elements, so the diagram draws pieces without a glyph the
+// same way the window and PNG diagrams do. A sprite is the glyph defined for the piece, scaled from
+// SQUARE_SIZE to the diagram's squares.
+struct SvgBackend {
+	svg: String,
+	size: (f64, f64)
+}
+
+impl SvgBackend {
+	fn new(size: (f64, f64)) -> SvgBackend {
+		SvgBackend {
+			svg: String::new(),
+			size: size
+		}
+	}
+}
+
+impl DrawBackend for SvgBackend {
+	fn get_size(&self) -> (f64, f64) {
+		self.size
+	}
+
+	fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		self.svg.push_str(&format!(
+			"<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>\n",
+			rect[0], rect[1], rect[2], rect[3], fill_attributes(color)
+		));
+	}
+
+	fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		self.svg.push_str(&format!(
+			"<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {}/>\n",
+			rect[0] + rect[2] / 2.0, rect[1] + rect[3] / 2.0, rect[2] / 2.0, rect[3] / 2.0, fill_attributes(color)
+		));
+	}
+
+	fn draw_sprite(&mut self, piece: &ChessPiece, position: (f64, f64), scale: f64) {
+		if let ChessPieceType::Fairy(_) = piece.get_type() {
+			draw_fallback_piece(self, piece, position, scale);
+			return;
+		}
+		self.svg.push_str(&format!(
+			"<use xlink:href=\"#{}\" transform=\"translate({},{}) scale({})\"/>\n",
+			glyph_id(piece), position.0, position.1, SQUARE_SIZE * scale / GLYPH_SIZE
+		));
+	}
+
+	fn draw_text(&mut self, text: &str, _: Font, font_size: u32, color: [f32; 4], position: (f64, f64)) {
+		let text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+		self.svg.push_str(&format!(
+			"<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" {}>{}</text>\n",
+			position.0, position.1, FONT_FAMILY, font_size, fill_attributes(color), text
+		));
+	}
+
+	fn draw_arrow(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [f32; 4]) {
+		let (shaft, head) = arrow_polygons(from, to, width);
+		for points in &[shaft, head] {
+			let points: Vec<String> = points.iter().map(|point| format!("{},{}", point[0], point[1])).collect();
+			self.svg.push_str(&format!("<polygon points=\"{}\" {}/>\n", points.join(" "), fill_attributes(color)));
+		}
+	}
+}
+
+fn hex_color(color: [f32; 4]) -> String {
+	format!(
+		"#{:02x}{:02x}{:02x}",
+		(color[0] * 255.0).round() as u8,
+		(color[1] * 255.0).round() as u8,
+		(color[2] * 255.0).round() as u8
+	)
+}
+
+fn fill_attributes(color: [f32; 4]) -> String {
+	if color[3] < 1.0 {
+		format!("fill=\"{}\" fill-opacity=\"{}\"", hex_color(color), color[3])
+	} else {
+		format!("fill=\"{}\"", hex_color(color))
+	}
+}
+
+fn stroke_attributes(color: [f32; 4]) -> String {
+	if color[3] < 1.0 {
+		format!("stroke=\"{}\" stroke-opacity=\"{}\"", hex_color(color), color[3])
+	} else {
+		format!("stroke=\"{}\"", hex_color(color))
+	}
+}
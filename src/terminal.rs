@@ -0,0 +1,135 @@
+use board::{ChessPiece, ChessPieceColor};
+use session::{Session, autosave_path};
+use variant::WinCondition;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+const HELP: &'static str = "Type a move in algebraic notation (Nf3, exd5, e8=Q, N@f3 to drop) or as squares (g1f3, e7e8q).
+Other commands:
+  moves    list the legal moves
+  board    draw the board again
+  ascii    draw pieces as letters
+  unicode  draw pieces as figurines
+  save     save the game to continue later, to the autosave file or 'save FILE'
+  help     show this message
+  quit     leave the game";
+
+// Plays a game on standard input and output, redrawing the board as text after every move,
+// for when there is no window to open (e.g. over SSH). Returns the session as it was left.
+pub fn play_in_terminal(session: Session) -> Session {
+	let mut session = session;
+	let stdin = io::stdin();
+	let mut turn_start = Instant::now();
+
+	println!("Type 'help' for the list of commands.");
+	print_position(&session);
+
+	loop {
+		if let Some(result) = session.game.get_result() {
+			println!("{} {}", result.get_score(), result.to_string());
+			break;
+		}
+
+		print!("{:?} to move> ", session.game.get_side_to_move());
+		let _ = io::stdout().flush();
+
+		let mut line = String::new();
+		match stdin.lock().read_line(&mut line) {
+			Ok(0) | Err(_) => { break; }, // End of input
+			Ok(_) => {}
+		}
+
+		// Time spent typing counts against the side to move
+		let elapsed = turn_start.elapsed();
+		turn_start = Instant::now();
+		session.game.update(elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9);
+		if session.game.get_result().is_some() {
+			continue;
+		}
+
+		match line.trim() {
+			"" => {},
+			"quit" | "exit" => { break; },
+			"help" => println!("{}", HELP),
+			"board" => print_position(&session),
+			"ascii" => {
+				session.ascii = true;
+				print_position(&session);
+			},
+			"unicode" => {
+				session.ascii = false;
+				print_position(&session);
+			},
+			"moves" => {
+				let game = &session.game;
+				let moves: Vec<String> = game.get_all_legal_moves().iter()
+					.map(|chess_move| game.get_algebraic_notation(chess_move))
+					.collect();
+				println!("{}", moves.join(" "));
+			},
+			"save" => save(&session, autosave_path()),
+			text if text.starts_with("save ") => save(&session, Some(PathBuf::from(text[5..].trim()))),
+			text => {
+				match session.game.parse_move(text).and_then(|chess_move| session.game.move_piece(&chess_move)) {
+					Ok(_) => print_position(&session),
+					Err(e) => println!("{}", e)
+				}
+			}
+		}
+	}
+
+	session
+}
+
+fn save(session: &Session, path: Option<PathBuf>) {
+	match path {
+		Some(path) => match session.save(&path) {
+			Ok(_) => println!("Saved to {}", path.display()),
+			Err(e) => println!("{}", e)
+		},
+		None => println!("There is no home folder to save to, use 'save FILE'")
+	}
+}
+
+// The board followed by the players' clocks, the pieces they hold when the rules have drops and the
+// last move played
+fn print_position(session: &Session) {
+	let game = &session.game;
+	let board = game.get_board();
+	if session.ascii {
+		println!("{:#}", board);
+	} else {
+		println!("{}", board);
+	}
+
+	println!(
+		"{} {}   {} {}",
+		session.white_name, game.get_clock(ChessPieceColor::White),
+		session.black_name, game.get_clock(ChessPieceColor::Black)
+	);
+
+	if game.get_rules().drops {
+		for &(name, color) in &[(&session.white_name, ChessPieceColor::White), (&session.black_name, ChessPieceColor::Black)] {
+			let pocket: String = game.get_pocket(color).iter()
+				.map(|&type_name| ChessPiece::new(type_name, color).to_letter())
+				.collect();
+			println!("{} holds: {}", name, if pocket.is_empty() { "-" } else { &pocket });
+		}
+	}
+
+	if game.get_rules().win_conditions.contains(&WinCondition::ThreeCheck) {
+		println!(
+			"Checks to win: {} {}   {} {}",
+			session.white_name, game.get_remaining_checks(ChessPieceColor::White),
+			session.black_name, game.get_remaining_checks(ChessPieceColor::Black)
+		);
+	}
+	if game.get_rules().win_conditions.contains(&WinCondition::KingOfTheHill) {
+		println!("A king on one of the centre squares wins");
+	}
+
+	if let Some(record) = game.get_history().last() {
+		println!("Last move: {}", record.notation);
+	}
+}
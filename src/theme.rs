@@ -0,0 +1,142 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Colors, fonts and the piece set used to draw the board, read from a manifest in resources/themes.
+//
+// A manifest is a list of `key = value` lines, blank lines and lines starting with '#' are ignored:
+//
+//     name = Classic
+//     light_square = #f0d9b5
+//     dark_square = #b58863
+//     last_move_highlight = #cdd26a99
+//     selected_highlight = #f6f66999
+//     legal_move_highlight = #00000033
+//     hill_highlight = #d9733340
+//     coordinate_font = DejaVuSans.ttf
+//     coordinate_font_size = 12
+//     coordinate_color = #404040
+//     piece_set = sprites
+//
+// Colors are #rrggbb or #rrggbbaa. The coordinate font is looked up in resources/fonts and the
+// piece set is a folder relative to resources holding sprites named like '0_queen.png'. Only the
+// 'sprites' set ships with the game, another set is a folder of the same twelve sprites.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+	pub name: String,
+	pub light_square: [f32; 4],
+	pub dark_square: [f32; 4],
+	pub last_move_highlight: [f32; 4],
+	pub selected_highlight: [f32; 4],
+	pub legal_move_highlight: [f32; 4],
+	pub hill_highlight: [f32; 4], // The centre squares of King of the Hill
+	pub coordinate_font: String,
+	pub coordinate_font_size: u32,
+	pub coordinate_color: [f32; 4],
+	pub piece_set: PathBuf
+}
+
+impl Default for Theme {
+	fn default() -> Theme {
+		Theme {
+			name: "Classic".to_string(),
+			light_square: [0.94, 0.85, 0.71, 1.0],
+			dark_square: [0.71, 0.53, 0.39, 1.0],
+			last_move_highlight: [0.8, 0.82, 0.42, 0.6],
+			selected_highlight: [0.96, 0.96, 0.41, 0.6],
+			legal_move_highlight: [0.0, 0.0, 0.0, 0.2],
+			hill_highlight: [0.85, 0.45, 0.2, 0.25],
+			coordinate_font: "DejaVuSans.ttf".to_string(),
+			coordinate_font_size: 12,
+			coordinate_color: [0.25, 0.25, 0.25, 1.0],
+			piece_set: PathBuf::from("sprites")
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl Theme {
+	// Keys missing from the manifest keep the value of the default theme
+	pub fn parse(manifest: &str) -> Result<Theme, String> {
+		let mut theme = Theme::default();
+
+		for (i, line) in manifest.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let (key, value) = match line.find('=') {
+				Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+				None => { return Err(format!("Line {}: expected 'key = value', found '{}'", i + 1, line)); }
+			};
+
+			match key {
+				"name" => { theme.name = value.to_string(); },
+				"light_square" => { theme.light_square = try!(parse_color(value)); },
+				"dark_square" => { theme.dark_square = try!(parse_color(value)); },
+				"last_move_highlight" => { theme.last_move_highlight = try!(parse_color(value)); },
+				"selected_highlight" => { theme.selected_highlight = try!(parse_color(value)); },
+				"legal_move_highlight" => { theme.legal_move_highlight = try!(parse_color(value)); },
+				"hill_highlight" => { theme.hill_highlight = try!(parse_color(value)); },
+				"coordinate_font" => { theme.coordinate_font = value.to_string(); },
+				"coordinate_font_size" => {
+					theme.coordinate_font_size = match value.parse() {
+						Ok(size) => size,
+						Err(_) => { return Err(format!("Line {}: invalid font size '{}'", i + 1, value)); }
+					};
+				},
+				"coordinate_color" => { theme.coordinate_color = try!(parse_color(value)); },
+				"piece_set" => { theme.piece_set = PathBuf::from(value); },
+				_ => { return Err(format!("Line {}: unknown key '{}'", i + 1, key)); }
+			}
+		}
+
+		Ok(theme)
+	}
+
+	pub fn load(path: &Path) -> Result<Theme, String> {
+		let mut manifest = String::new();
+		match File::open(path).and_then(|mut file| file.read_to_string(&mut manifest)) {
+			Ok(_) => {},
+			Err(e) => { return Err(format!("Could not read theme {}: {}", path.display(), e)); }
+		}
+
+		Theme::parse(&manifest).map_err(|e| format!("{}: {}", path.display(), e))
+	}
+
+	// Every '.theme' manifest in a folder, sorted by name. Manifests that fail to load are skipped.
+	pub fn load_all(folder: &Path) -> Vec<Theme> {
+		let mut themes: Vec<Theme> = match fs::read_dir(folder) {
+			Ok(entries) => entries
+				.filter_map(|entry| entry.ok())
+				.map(|entry| entry.path())
+				.filter(|path| path.extension().map_or(false, |extension| extension == "theme"))
+				.filter_map(|path| Theme::load(&path).ok())
+				.collect(),
+			Err(_) => Vec::new()
+		};
+
+		themes.sort_by(|a, b| a.name.cmp(&b.name));
+		themes
+	}
+}
+
+// Parses '#rrggbb' or '#rrggbbaa' into an rgba color
+pub fn parse_color(value: &str) -> Result<[f32; 4], String> {
+	let hex = value.trim_left_matches('#');
+	// Only hex digits, so the components can be sliced out by byte
+	if value.len() == hex.len() || (hex.len() != 6 && hex.len() != 8) || !hex.chars().all(|letter| letter.is_digit(16)) {
+		return Err(format!("Invalid color '{}', expected #rrggbb or #rrggbbaa", value));
+	}
+
+	let mut color = [1.0f32; 4];
+	for i in 0..hex.len() / 2 {
+		color[i] = match u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16) {
+			Ok(component) => component as f32 / 255.0,
+			Err(_) => { return Err(format!("Invalid color '{}', expected #rrggbb or #rrggbbaa", value)); }
+		};
+	}
+
+	Ok(color)
+}
@@ -0,0 +1,97 @@
+use error::ChessError;
+use fen::STARTING_FEN;
+use game::ChessGame;
+use search::search;
+use std::io::{self, BufRead, Write};
+
+// Depth searched when 'go' does not ask for one
+const DEFAULT_DEPTH: u32 = 3;
+
+// Speaks the Universal Chess Interface on standard input and output, so the engine can be used from
+// chess GUIs. Only fixed depth searches are supported, time controls are searched to the default depth.
+pub fn run_uci() {
+	let mut game = ChessGame::from_fen(STARTING_FEN).unwrap();
+	let mut chess960 = false; // Set through the UCI_Chess960 option, castling is then sent as king takes rook
+	let stdin = io::stdin();
+
+	for line in stdin.lock().lines() {
+		let line = match line {
+			Ok(line) => line,
+			Err(_) => { break; }
+		};
+		let words: Vec<&str> = line.split_whitespace().collect();
+
+		match words.first() {
+			Some(&"uci") => {
+				println!("id name rust_chess");
+				println!("id author Jason Schein");
+				println!("option name UCI_Chess960 type check default false");
+				println!("uciok");
+			},
+			Some(&"isready") => println!("readyok"),
+			Some(&"setoption") => {
+				// setoption name <name> value <value>
+				if let (Some(&"UCI_Chess960"), Some(&value)) = (words.get(2), words.get(4)) {
+					chess960 = value == "true";
+					game.set_chess960(chess960);
+				}
+			},
+			Some(&"ucinewgame") => {
+				game = ChessGame::from_fen(STARTING_FEN).unwrap();
+				game.set_chess960(chess960);
+			},
+			Some(&"position") => {
+				match parse_position(&words[1..], chess960) {
+					Ok(position) => { game = position; },
+					Err(e) => println!("info string {}", e)
+				}
+			},
+			Some(&"go") => {
+				let depth = words.iter()
+					.position(|&word| word == "depth")
+					.and_then(|i| words.get(i + 1))
+					.and_then(|depth| depth.parse::<u32>().ok())
+					.unwrap_or(DEFAULT_DEPTH);
+
+				let result = search(&game, depth);
+				let pv: Vec<String> = result.principal_variation.iter()
+					.map(|chess_move| game.get_coordinate_notation(chess_move))
+					.collect();
+				let score = match result.get_mate_in() {
+					Some(moves) => format!("mate {}", moves),
+					None => format!("cp {}", result.score)
+				};
+				println!("info depth {} score {} nodes {} pv {}", depth, score, result.nodes, pv.join(" "));
+
+				match result.best_move {
+					Some(chess_move) => println!("bestmove {}", game.get_coordinate_notation(&chess_move)),
+					None => println!("bestmove 0000")
+				}
+			},
+			Some(&"quit") => { break; },
+			_ => {} // Unknown commands are ignored, as the protocol asks
+		}
+
+		let _ = io::stdout().flush();
+	}
+}
+
+// 'startpos' or 'fen <fields>', optionally followed by 'moves' and the moves played since
+fn parse_position(words: &[&str], chess960: bool) -> Result<ChessGame, ChessError> {
+	let moves_index = words.iter().position(|&word| word == "moves").unwrap_or(words.len());
+	let mut game = match words.first() {
+		Some(&"startpos") => ChessGame::from_fen(STARTING_FEN).unwrap(),
+		Some(&"fen") => try!(ChessGame::from_fen(&words[1..moves_index].join(" "))),
+		_ => { return Err(ChessError::InvalidFen("position needs 'startpos' or 'fen'".to_string())); }
+	};
+	if chess960 {
+		game.set_chess960(true);
+	}
+
+	for text in words.iter().skip(moves_index + 1) {
+		let chess_move = try!(game.parse_move(text));
+		try!(game.apply_move(&chess_move));
+	}
+
+	Ok(game)
+}
@@ -0,0 +1,145 @@
+use board::*;
+use error::ChessError;
+use game::{CastlingRight, ChessGame};
+use std::fmt;
+
+// Something about a position that could not come about in a game
+#[derive(Debug, Clone, PartialEq)]
+pub enum PositionProblem {
+	KingCount(ChessPieceColor, usize), // A side without exactly one king
+	PawnOnBackRank(BoardCoordinates), // Pawns can neither stand on their own back rank nor stay on the last one
+	OpponentInCheck(ChessPieceColor), // The side that just moved left its king in check
+	TooManyPieces(ChessPieceColor, usize, usize), // The count, and the most a side starts with
+	TooManyPawns(ChessPieceColor, usize, usize),
+	TooManyPromotions(ChessPieceColor, usize), // More pieces beyond the starting set than missing pawns to promote
+	CastlingRight(CastlingRight), // A castling right without the king and rook on their home row
+	EnPassant(BoardCoordinates) // An en passant square no pawn can have just skipped over
+}
+
+impl fmt::Display for PositionProblem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			&PositionProblem::KingCount(color, count) => write!(f, "{:?} has {} kings instead of 1", color, count),
+			&PositionProblem::PawnOnBackRank(coordinates) => write!(f, "Pawn on the back rank at {}", coordinates.to_string()),
+			&PositionProblem::OpponentInCheck(color) => write!(f, "{:?} is in check but it is not their move", color),
+			&PositionProblem::TooManyPieces(color, count, most) => write!(f, "{:?} has {} pieces, more than {}", color, count, most),
+			&PositionProblem::TooManyPawns(color, count, most) => write!(f, "{:?} has {} pawns, more than {}", color, count, most),
+			&PositionProblem::TooManyPromotions(color, count) => {
+				write!(f, "{:?} has {} promoted pieces but not enough missing pawns to have promoted them", color, count)
+			},
+			&PositionProblem::CastlingRight(right) => {
+				write!(f, "{:?} can not castle with the rook on {}", right.color, right.rook.to_string())
+			},
+			&PositionProblem::EnPassant(coordinates) => write!(f, "No pawn can have just skipped over {}", coordinates.to_string())
+		}
+	}
+}
+
+impl ChessGame {
+	// Every way the position breaks the rules, empty for a position that could be reached in a game.
+	// Positions set up by hand or read from a FEN should be checked before they are played.
+	pub fn get_position_problems(&self) -> Vec<PositionProblem> {
+		let mut problems = Vec::new();
+		let board = self.get_board();
+		let last_row = board.get_height() - 1;
+		// Each side starts with at most a row of pawns and a row of pieces, the rest of a side's pieces
+		// must have come from promoted pawns. With drops captured pieces change sides, so there is no limit.
+		let starting_pawns = board.get_width() as usize;
+		let starting_pieces = starting_pawns * 2;
+		let limit_counts = !self.get_rules().drops;
+
+		for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+			let pieces: Vec<ChessPiece> = board.get_all_coordinates().iter()
+				.filter_map(|coordinates| match board.get_contents_at_coordinates(coordinates) {
+					Ok(&Some(piece)) if piece.get_color() == color => Some(piece),
+					_ => None
+				})
+				.collect();
+			let count = |type_name: ChessPieceType| pieces.iter().filter(|piece| piece.get_type() == type_name).count();
+
+			// Without a royal king a side may have any number of kings, as in antichess
+			let kings = count(ChessPieceType::King);
+			if kings != 1 && self.get_rules().royal_king {
+				problems.push(PositionProblem::KingCount(color, kings));
+			}
+
+			if limit_counts && pieces.len() > starting_pieces {
+				problems.push(PositionProblem::TooManyPieces(color, pieces.len(), starting_pieces));
+			}
+
+			let pawns = count(ChessPieceType::Pawn);
+			if limit_counts && pawns > starting_pawns {
+				problems.push(PositionProblem::TooManyPawns(color, pawns, starting_pawns));
+			}
+
+			// Anything past the starting set of a piece type must be a promoted pawn
+			let starting_counts = [
+				(ChessPieceType::Queen, 1),
+				(ChessPieceType::Rook, 2),
+				(ChessPieceType::Bishop, 2),
+				(ChessPieceType::Knight, 2)
+			];
+			let promoted = starting_counts.iter()
+				.map(|&(type_name, starting)| count(type_name).saturating_sub(starting))
+				.sum::<usize>();
+			if limit_counts && promoted > starting_pawns.saturating_sub(pawns) {
+				problems.push(PositionProblem::TooManyPromotions(color, promoted));
+			}
+		}
+
+		for coordinates in board.get_all_coordinates() {
+			if let Ok(&Some(piece)) = board.get_contents_at_coordinates(&coordinates) {
+				if piece.get_type() == ChessPieceType::Pawn && (coordinates.row == 0 || coordinates.row == last_row) {
+					problems.push(PositionProblem::PawnOnBackRank(coordinates));
+				}
+			}
+		}
+
+		let waiting = self.get_side_to_move().opposite_color();
+		if self.is_in_check(waiting) {
+			problems.push(PositionProblem::OpponentInCheck(waiting));
+		}
+
+		for right in self.get_castling_rights() {
+			if !self.is_castling_right_possible(right) {
+				problems.push(PositionProblem::CastlingRight(*right));
+			}
+		}
+
+		if let Some(en_passant) = self.get_en_passant() {
+			if !self.is_en_passant_possible(&en_passant) {
+				problems.push(PositionProblem::EnPassant(en_passant));
+			}
+		}
+
+		problems
+	}
+
+	// Ok for a position that could be reached in a game, otherwise an error listing every problem with it
+	pub fn validate_position(&self) -> Result<(), ChessError> {
+		let problems = self.get_position_problems();
+		if problems.is_empty() {
+			Ok(())
+		} else {
+			Err(ChessError::IllegalPosition(problems))
+		}
+	}
+
+	// Whether the side that just moved can have double stepped a pawn over `en_passant`: the pawn
+	// stands just beyond it, with the square it skipped and the one it came from empty. The pawn
+	// stepped from the rules' double step rank, so the skipped square is on the rank after it.
+	fn is_en_passant_possible(&self, en_passant: &BoardCoordinates) -> bool {
+		let board = self.get_board();
+		let mover = self.get_side_to_move().opposite_color();
+		let direction = self.get_pawn_direction(mover);
+		let double_step_rank = self.get_rules().double_step_rank;
+		let skipped_row = self.get_home_row(mover) as i8 + direction * double_step_rank as i8;
+		if double_step_rank == 0 || en_passant.row as i8 != skipped_row {
+			return false;
+		}
+
+		let pawn = ChessPiece::new(ChessPieceType::Pawn, mover);
+		let at = |vertical: i8| board.get_move_destination(en_passant, 0, vertical).map(|cell| *cell.get_contents());
+		at(0) == Some(None) && at(-direction) == Some(None) && at(direction) == Some(Some(pawn))
+	}
+}
@@ -0,0 +1,284 @@
+use board::*;
+use error::ChessError;
+use fairy::PieceDefinition;
+use fen::{STARTING_FEN, piece_from_letter};
+use game::ChessGame;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+// How a game ends for the player who has no legal moves but is not in check
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum StalemateRule {
+	Draw,
+	Win,
+	Loss
+}
+
+// Ways of winning the game on the board, as opposed to on time
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum WinCondition {
+	Checkmate,
+	KingOfTheHill, // Moving the king onto one of the centre squares
+	ThreeCheck // Giving check for the third time
+}
+
+// The rules a game is played by that are not given by its position. The default is standard chess.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Rules {
+	pub promotion_pieces: Vec<ChessPieceType>, // In the order they are offered
+	pub promotion_ranks: u8, // Ranks at the far end a pawn may promote on, it has to on the last one
+	pub double_step_rank: u8, // Counted from the owner's side, 0 when pawns never advance two squares
+	pub castling: bool,
+	pub drops: bool, // Captured pieces change sides and may be put back on the board, as in Crazyhouse
+	pub captures_to_partner: bool, // With drops, captured pieces go to the partner on the other bughouse board
+	pub explosions: bool, // Captures blow up the pieces around them, as in atomic chess
+	pub royal_king: bool, // Otherwise the king is an ordinary piece that may be left in check and taken
+	pub forced_captures: bool, // A player who can capture has to, as in antichess
+	pub win_conditions: Vec<WinCondition>,
+	pub stalemate: StalemateRule
+}
+
+impl Default for Rules {
+	fn default() -> Rules {
+		Rules {
+			promotion_pieces: PROMOTION_CHOICES.to_vec(),
+			promotion_ranks: 1,
+			double_step_rank: 2,
+			castling: true,
+			drops: false,
+			captures_to_partner: false,
+			explosions: false,
+			royal_king: true,
+			forced_captures: false,
+			win_conditions: vec![WinCondition::Checkmate],
+			stalemate: StalemateRule::Draw
+		}
+	}
+}
+
+// A chess variant read from a definition in resources/variants, so new variants can be tried out
+// without changing the move generator.
+//
+// A definition is a list of `key = value` lines like a theme manifest, blank lines and lines
+// starting with '#' are ignored:
+//
+//     name = Capablanca
+//     width = 10
+//     height = 8
+//     piece = Archbishop, A, BN
+//     piece = Chancellor, C, RN
+//     start = rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1
+//     promotion = QCARBN
+//     promotion_ranks = 1
+//     double_step_rank = 2
+//     castling = true
+//     drops = false
+//     explosions = false
+//     royal_king = true
+//     forced_captures = false
+//     win = checkmate
+//     stalemate = draw
+//
+// `win` lists the ways of winning other than on time: checkmate, king_of_the_hill and three_check,
+// or none when only the stalemate rule decides the game. A king that is not royal may also be
+// promoted to.
+// Each `piece` line defines a fairy piece by its name, letter and Betza notation, optionally
+// followed by its value in pawns. The start position is a FEN using those letters, and `promotion`
+// lists the letters of the pieces a pawn may become. Only the name and the start position are
+// required, everything else is as in standard chess when left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+	pub name: String,
+	pub width: u8,
+	pub height: u8,
+	pub pieces: Vec<PieceDefinition>,
+	pub start: String, // The starting position as FEN
+	pub rules: Rules
+}
+
+impl Default for Variant {
+	fn default() -> Variant {
+		Variant {
+			name: "Standard".to_string(),
+			width: 8,
+			height: 8,
+			pieces: Vec::new(),
+			start: STARTING_FEN.to_string(),
+			rules: Rules::default()
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl Variant {
+	pub fn parse(definition: &str) -> Result<Variant, String> {
+		let mut variant = Variant::default();
+		let mut name = None;
+		let mut start = None;
+		let mut dimensions = (None, None);
+		let mut promotion = None;
+
+		for (i, line) in definition.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let (key, value) = match line.find('=') {
+				Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+				None => { return Err(format!("Line {}: expected 'key = value', found '{}'", i + 1, line)); }
+			};
+			let invalid = |what: &str| format!("Line {}: invalid {} '{}'", i + 1, what, value);
+
+			match key {
+				"name" => { name = Some(value.to_string()); },
+				"width" => { dimensions.0 = Some(try!(value.parse::<u8>().map_err(|_| invalid("width")))); },
+				"height" => { dimensions.1 = Some(try!(value.parse::<u8>().map_err(|_| invalid("height")))); },
+				"piece" => {
+					let piece = try!(parse_piece(value).map_err(|e| format!("Line {}: {}", i + 1, e)));
+					if variant.pieces.iter().any(|defined| defined.get_letter() == piece.get_letter()) {
+						return Err(format!("Line {}: there is already a piece written '{}'", i + 1, piece.get_letter()));
+					}
+					variant.pieces.push(piece);
+				},
+				"start" => { start = Some(value.to_string()); },
+				// Read once all the pieces are known, since the letters may be of fairy pieces
+				"promotion" => { promotion = Some((i + 1, value.to_string())); },
+				"promotion_ranks" => {
+					variant.rules.promotion_ranks = match value.parse::<u8>() {
+						Ok(ranks) if ranks > 0 => ranks,
+						_ => { return Err(invalid("number of promotion ranks")); }
+					};
+				},
+				"double_step_rank" => {
+					variant.rules.double_step_rank = try!(value.parse::<u8>().map_err(|_| invalid("double step rank")));
+				},
+				"castling" => { variant.rules.castling = try!(value.parse::<bool>().map_err(|_| invalid("castling setting"))); },
+				"drops" => { variant.rules.drops = try!(value.parse::<bool>().map_err(|_| invalid("drops setting"))); },
+				"explosions" => { variant.rules.explosions = try!(value.parse::<bool>().map_err(|_| invalid("explosions setting"))); },
+				"royal_king" => { variant.rules.royal_king = try!(value.parse::<bool>().map_err(|_| invalid("royal king setting"))); },
+				"forced_captures" => {
+					variant.rules.forced_captures = try!(value.parse::<bool>().map_err(|_| invalid("forced captures setting")));
+				},
+				"win" => {
+					let mut conditions = Vec::new();
+					for condition in value.split(',').map(|condition| condition.trim()).filter(|&condition| condition != "none") {
+						conditions.push(match condition {
+							"checkmate" => WinCondition::Checkmate,
+							"king_of_the_hill" => WinCondition::KingOfTheHill,
+							"three_check" => WinCondition::ThreeCheck,
+							_ => { return Err(format!("Line {}: unknown win condition '{}'", i + 1, condition)); }
+						});
+					}
+					variant.rules.win_conditions = conditions;
+				},
+				"stalemate" => {
+					variant.rules.stalemate = match value {
+						"draw" => StalemateRule::Draw,
+						"win" => StalemateRule::Win,
+						"loss" => StalemateRule::Loss,
+						_ => { return Err(invalid("stalemate rule, expected draw, win or loss")); }
+					};
+				},
+				_ => { return Err(format!("Line {}: unknown key '{}'", i + 1, key)); }
+			}
+		}
+
+		variant.name = try!(name.ok_or("The variant has no name".to_string()));
+		variant.start = try!(start.ok_or(format!("{} has no start position", variant.name)));
+
+		if let Some((line, letters)) = promotion {
+			variant.rules.promotion_pieces = Vec::new();
+			for letter in letters.chars().filter(|letter| !letter.is_whitespace()) {
+				let type_name = match piece_from_letter(letter.to_ascii_uppercase()) {
+					Some(piece) if piece.get_type() == ChessPieceType::King && !variant.rules.royal_king => piece.get_type(),
+					Some(piece) if piece.get_type() != ChessPieceType::Pawn && piece.get_type() != ChessPieceType::King => piece.get_type(),
+					Some(..) => { return Err(format!("Line {}: pawns can not be promoted to '{}'", line, letter)); },
+					None => match variant.pieces.iter().find(|piece| piece.get_letter() == letter.to_ascii_uppercase()) {
+						Some(piece) => piece.get_type(),
+						None => { return Err(format!("Line {}: no piece is written '{}'", line, letter)); }
+					}
+				};
+				variant.rules.promotion_pieces.push(type_name);
+			}
+			if variant.rules.promotion_pieces.is_empty() {
+				return Err(format!("Line {}: pawns need at least one piece to promote to", line));
+			}
+		}
+
+		// The start position has to be playable and fit the board the variant describes
+		let game = try!(ChessGame::from_variant(&variant).map_err(|e| e.to_string()));
+		let (width, height) = game.get_board().get_dimensions();
+		variant.width = dimensions.0.unwrap_or(width);
+		variant.height = dimensions.1.unwrap_or(height);
+		if (variant.width, variant.height) != (width, height) {
+			return Err(format!("The start position is {}x{} but the board is {}x{}", width, height, variant.width, variant.height));
+		}
+		if variant.rules.promotion_ranks >= height {
+			return Err(format!("Pawns can not promote on {} of {} ranks", variant.rules.promotion_ranks, height));
+		}
+
+		Ok(variant)
+	}
+
+	pub fn load(path: &Path) -> Result<Variant, String> {
+		let mut definition = String::new();
+		match File::open(path).and_then(|mut file| file.read_to_string(&mut definition)) {
+			Ok(_) => {},
+			Err(e) => { return Err(format!("Could not read variant {}: {}", path.display(), e)); }
+		}
+
+		Variant::parse(&definition).map_err(|e| format!("{}: {}", path.display(), e))
+	}
+
+	// Every '.variant' definition in a folder, sorted by name. Definitions that fail to load are skipped.
+	pub fn load_all(folder: &Path) -> Vec<Variant> {
+		let mut variants: Vec<Variant> = match fs::read_dir(folder) {
+			Ok(entries) => entries
+				.filter_map(|entry| entry.ok())
+				.map(|entry| entry.path())
+				.filter(|path| path.extension().map_or(false, |extension| extension == "variant"))
+				.filter_map(|path| Variant::load(&path).ok())
+				.collect(),
+			Err(_) => Vec::new()
+		};
+
+		variants.sort_by(|a, b| a.name.cmp(&b.name));
+		variants
+	}
+}
+
+// A piece line's 'name, letter, betza' with an optional value after them
+fn parse_piece(value: &str) -> Result<PieceDefinition, String> {
+	let fields: Vec<&str> = value.split(',').map(|field| field.trim()).collect();
+	if fields.len() < 3 || fields.len() > 4 {
+		return Err(format!("expected 'name, letter, betza' with an optional value, found '{}'", value));
+	}
+
+	let mut letters = fields[1].chars();
+	let letter = match (letters.next(), letters.next()) {
+		(Some(letter), None) => letter,
+		_ => { return Err(format!("'{}' is not a single letter", fields[1])); }
+	};
+
+	let piece = try!(PieceDefinition::new(fields[0], letter, fields[2]).map_err(|e| e.to_string()));
+	match fields.get(3) {
+		Some(value) => value.parse::<u32>()
+			.map(|value| piece.with_value(value))
+			.map_err(|_| format!("'{}' is not a piece value", value)),
+		None => Ok(piece)
+	}
+}
+
+impl ChessGame {
+	// A new game of a variant from its start position
+	pub fn from_variant(variant: &Variant) -> Result<ChessGame, ChessError> {
+		let mut game = try!(ChessGame::from_fen_with_pieces(&variant.start, variant.pieces.clone()));
+		game.set_rules(variant.rules.clone());
+		Ok(game)
+	}
+}
@@ -1,23 +1,374 @@
-use opengl_graphics::GlGraphics;
-use piston_window::*;
+use board::{ChessPiece, ChessPieceType, ChessPieceColor, BoardCoordinates};
+use std::path::PathBuf;
+use theme::Theme;
 
-struct View {
-	g: GlGraphics
+// Side length in pixels of a single board square, matches the size of the piece sprites
+pub const SQUARE_SIZE: f64 = 65.0;
+
+// Top left corner of the board inside the window
+pub const BOARD_ORIGIN: (f64, f64) = (40.0, 100.0);
+
+// Room beside and below the board for the coordinates, as much as BOARD_ORIGIN leaves to its left
+const BOARD_MARGIN: f64 = 40.0;
+
+pub trait Renderable {
+	fn draw(&self, backend: &mut DrawBackend, theme: &Theme);
+}
+
+// Which of the theme's fonts a piece of text is drawn in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+	Interface,
+	Coordinates
+}
+
+// The drawing primitives everything on screen is built from. Positions are in window pixels
+// with the origin at the top left, colors are rgba.
+pub trait DrawBackend {
+	// Width and height of the area being drawn to
+	fn get_size(&self) -> (f64, f64);
+
+	// rect is [x, y, width, height]
+	fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]);
+
+	// Fills the ellipse inside rect
+	fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]);
+
+	// Draws the sprite of a piece with its top left corner at `position`, scale 1.0 being one square
+	fn draw_sprite(&mut self, piece: &ChessPiece, position: (f64, f64), scale: f64);
+
+	// Draws a line of text with its baseline starting at `position`
+	fn draw_text(&mut self, text: &str, font: Font, font_size: u32, color: [f32; 4], position: (f64, f64));
+
+	// A straight arrow from the centre of `from` to the tip at `to`
+	fn draw_arrow(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [f32; 4]);
 }
 
-impl View {
-	pub fn new(g: GlGraphics) -> View {
-		View {
-			g: g
+// Draws through another backend with everything moved by `offset`, so a board laid out at
+// BOARD_ORIGIN can be shown elsewhere in the window, e.g. the second board of a bughouse game
+pub struct OffsetBackend<'a> {
+	backend: &'a mut DrawBackend,
+	offset: (f64, f64)
+}
+
+impl<'a> OffsetBackend<'a> {
+	pub fn new(backend: &'a mut DrawBackend, offset: (f64, f64)) -> OffsetBackend<'a> {
+		OffsetBackend {
+			backend: backend,
+			offset: offset
 		}
 	}
 
-	pub fn render<T: Renderable>(&mut self, c: Context, r: T) {
+	fn shift(&self, position: (f64, f64)) -> (f64, f64) {
+		(position.0 + self.offset.0, position.1 + self.offset.1)
+	}
+}
+
+impl<'a> DrawBackend for OffsetBackend<'a> {
+	fn get_size(&self) -> (f64, f64) {
+		self.backend.get_size()
+	}
+
+	fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		self.backend.draw_rect([rect[0] + self.offset.0, rect[1] + self.offset.1, rect[2], rect[3]], color);
+	}
+
+	fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		self.backend.draw_ellipse([rect[0] + self.offset.0, rect[1] + self.offset.1, rect[2], rect[3]], color);
+	}
+
+	fn draw_sprite(&mut self, piece: &ChessPiece, position: (f64, f64), scale: f64) {
+		let position = self.shift(position);
+		self.backend.draw_sprite(piece, position, scale);
+	}
+
+	fn draw_text(&mut self, text: &str, font: Font, font_size: u32, color: [f32; 4], position: (f64, f64)) {
+		let position = self.shift(position);
+		self.backend.draw_text(text, font, font_size, color, position);
+	}
 
+	fn draw_arrow(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [f32; 4]) {
+		let (from, to) = (self.shift(from), self.shift(to));
+		self.backend.draw_arrow(from, to, width, color);
 	}
 }
 
-pub trait Renderable {
-	fn draw(&self, window: &PistonWindow);
+// Draws through another backend with everything scaled by `scale`, so a window laid out for squares
+// of SQUARE_SIZE can be shown smaller, e.g. when a board has too many ranks to fit otherwise
+pub struct ScaledBackend<'a> {
+	backend: &'a mut DrawBackend,
+	scale: f64
+}
+
+impl<'a> ScaledBackend<'a> {
+	pub fn new(backend: &'a mut DrawBackend, scale: f64) -> ScaledBackend<'a> {
+		ScaledBackend {
+			backend: backend,
+			scale: scale
+		}
+	}
+
+	fn scale_point(&self, position: (f64, f64)) -> (f64, f64) {
+		(position.0 * self.scale, position.1 * self.scale)
+	}
+
+	fn scale_rect(&self, rect: [f64; 4]) -> [f64; 4] {
+		[rect[0] * self.scale, rect[1] * self.scale, rect[2] * self.scale, rect[3] * self.scale]
+	}
+}
+
+impl<'a> DrawBackend for ScaledBackend<'a> {
+	fn get_size(&self) -> (f64, f64) {
+		let (width, height) = self.backend.get_size();
+		(width / self.scale, height / self.scale)
+	}
+
+	fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		let rect = self.scale_rect(rect);
+		self.backend.draw_rect(rect, color);
+	}
+
+	fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+		let rect = self.scale_rect(rect);
+		self.backend.draw_ellipse(rect, color);
+	}
+
+	fn draw_sprite(&mut self, piece: &ChessPiece, position: (f64, f64), scale: f64) {
+		let position = self.scale_point(position);
+		self.backend.draw_sprite(piece, position, scale * self.scale);
+	}
+
+	fn draw_text(&mut self, text: &str, font: Font, font_size: u32, color: [f32; 4], position: (f64, f64)) {
+		let position = self.scale_point(position);
+		let font_size = (font_size as f64 * self.scale).round().max(1.0) as u32;
+		self.backend.draw_text(text, font, font_size, color, position);
+	}
+
+	fn draw_arrow(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [f32; 4]) {
+		let (from, to) = (self.scale_point(from), self.scale_point(to));
+		self.backend.draw_arrow(from, to, width * self.scale, color);
+	}
+}
+
+// Width and height of a window layout with a board of `dimensions` columns and rows at BOARD_ORIGIN
+// and `panel_width` to its right, when squares are SQUARE_SIZE
+pub fn board_layout_size(dimensions: (u8, u8), panel_width: f64) -> (f64, f64) {
+	let (width, height) = BoardLayout::window(dimensions, false).get_total_size();
+	(width - BOARD_MARGIN + panel_width, height)
+}
+
+// Side length on screen of a square, for a layout of `layout_size` (see `board_layout_size`) shown
+// in a window of `window_size`. Squares shrink until the whole layout fits, but never grow past the
+// size of the sprites.
+pub fn fit_square_size(layout_size: (f64, f64), window_size: (f64, f64)) -> f64 {
+	let scale = (window_size.0 / layout_size.0).min(window_size.1 / layout_size.1).min(1.0);
+	SQUARE_SIZE * scale
 }
 
+// Where a board is drawn: the top left corner of its first square, the side length of a square and
+// which way round it is, with `margin` left beside and below the board for its coordinates. Row 0
+// (white's side) is drawn at the bottom, unless the board is flipped to show black's side there,
+// which turns it half way round.
+#[derive(Debug, Clone, Copy)]
+pub struct BoardLayout {
+	pub dimensions: (u8, u8), // Columns and rows
+	pub origin: (f64, f64),
+	pub square_size: f64,
+	pub margin: f64,
+	pub flipped: bool
+}
+
+impl BoardLayout {
+	// The board of a window, at BOARD_ORIGIN with squares of SQUARE_SIZE
+	pub fn window(dimensions: (u8, u8), flipped: bool) -> BoardLayout {
+		BoardLayout {
+			dimensions: dimensions,
+			origin: BOARD_ORIGIN,
+			square_size: SQUARE_SIZE,
+			margin: BOARD_MARGIN,
+			flipped: flipped
+		}
+	}
+
+	// A standalone diagram of the board, with `margin` on every side of it
+	pub fn diagram(dimensions: (u8, u8), square_size: f64, margin: f64, flipped: bool) -> BoardLayout {
+		BoardLayout {
+			dimensions: dimensions,
+			origin: (margin, margin),
+			square_size: square_size,
+			margin: margin,
+			flipped: flipped
+		}
+	}
+
+	// Position of the top left corner of a square
+	pub fn square_position(&self, coordinates: &BoardCoordinates) -> (f64, f64) {
+		let (last_col, last_row) = (self.dimensions.0 - 1, self.dimensions.1 - 1);
+		let (col, row) = if self.flipped {
+			(last_col - coordinates.col, coordinates.row)
+		} else {
+			(coordinates.col, last_row - coordinates.row)
+		};
+
+		(self.origin.0 + col as f64 * self.square_size, self.origin.1 + row as f64 * self.square_size)
+	}
+
+	pub fn square_centre(&self, coordinates: &BoardCoordinates) -> (f64, f64) {
+		let (x, y) = self.square_position(coordinates);
+		(x + self.square_size / 2.0, y + self.square_size / 2.0)
+	}
+
+	// The square under a position, if there is one
+	pub fn square_at_position(&self, position: (f64, f64)) -> Option<BoardCoordinates> {
+		let x = position.0 - self.origin.0;
+		let y = position.1 - self.origin.1;
+		let (board_width, board_height) = self.get_board_size();
+
+		if x < 0.0 || y < 0.0 || x >= board_width || y >= board_height {
+			return None;
+		}
+
+		let (last_col, last_row) = (self.dimensions.0 - 1, self.dimensions.1 - 1);
+		let col = (x / self.square_size) as u8;
+		let row = (y / self.square_size) as u8;
+		if self.flipped {
+			Some((last_col - col, row).into())
+		} else {
+			Some((col, last_row - row).into())
+		}
+	}
+
+	pub fn get_board_size(&self) -> (f64, f64) {
+		(self.dimensions.0 as f64 * self.square_size, self.dimensions.1 as f64 * self.square_size)
+	}
+
+	// Width and height from the top left corner of the window or diagram to the far side of the margin
+	pub fn get_total_size(&self) -> (f64, f64) {
+		let (board_width, board_height) = self.get_board_size();
+		(self.origin.0 + board_width + self.margin, self.origin.1 + board_height + self.margin)
+	}
+}
+
+// The resources folder holding sprites, fonts and themes, searched for from the working directory
+pub fn resources_folder() -> PathBuf {
+	::find_folder::Search::ParentsThenKids(3, 3)
+		.for_folder("resources").unwrap()
+}
+
+// Sprites are named like '0_queen.png', where 0 is white and 1 is black. Piece sets only come with
+// the orthodox pieces, fairy pieces are drawn with `draw_fallback_piece`.
+pub fn sprite_file_name(piece: &ChessPiece) -> String {
+	let color = match piece.get_color() {
+		ChessPieceColor::White => 0,
+		ChessPieceColor::Black => 1
+	};
+	let type_name = match piece.get_type() {
+		ChessPieceType::Pawn => "pawn",
+		ChessPieceType::Rook => "rook",
+		ChessPieceType::Knight => "knight",
+		ChessPieceType::Bishop => "bishop",
+		ChessPieceType::Queen => "queen",
+		ChessPieceType::King => "king",
+		ChessPieceType::Fairy(letter) => { return format!("{}_fairy_{}.png", color, letter.to_ascii_lowercase()); }
+	};
+
+	format!("{}_{}.png", color, type_name)
+}
+
+// A disc in the piece's color with its letter on it, for pieces without a sprite
+pub fn draw_fallback_piece(backend: &mut DrawBackend, piece: &ChessPiece, position: (f64, f64), scale: f64) {
+	let size = SQUARE_SIZE * scale;
+	let (fill, ink) = match piece.get_color() {
+		ChessPieceColor::White => ([1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]),
+		ChessPieceColor::Black => ([0.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0])
+	};
+
+	let outline = size * 0.1;
+	backend.draw_ellipse([position.0 + outline, position.1 + outline, size - outline * 2.0, size - outline * 2.0], ink);
+	let inner = outline + size * 0.04;
+	backend.draw_ellipse([position.0 + inner, position.1 + inner, size - inner * 2.0, size - inner * 2.0], fill);
+
+	let font_size = (size * 0.45) as u32;
+	let letter = piece.to_letter().to_ascii_uppercase().to_string();
+	let text_position = (position.0 + size / 2.0 - font_size as f64 * 0.35, position.1 + size / 2.0 + font_size as f64 * 0.35);
+	backend.draw_text(&letter, Font::Interface, font_size, ink, text_position);
+}
+
+pub fn draw_board_squares(backend: &mut DrawBackend, theme: &Theme, layout: &BoardLayout) {
+	for col in 0..layout.dimensions.0 {
+		for row in 0..layout.dimensions.1 {
+			// a1 (0, 0) is a dark square
+			let color = if (col + row) % 2 == 0 { theme.dark_square } else { theme.light_square };
+			highlight_square(backend, layout, &(col, row).into(), color);
+		}
+	}
+}
+
+// File letters below the board and rank numbers to its left, each centred in the margin
+pub fn draw_board_coordinates(backend: &mut DrawBackend, theme: &Theme, layout: &BoardLayout, font_size: u32) {
+	let font_size = font_size as f64;
+	// Glyphs are taken to be two thirds of the font size wide
+	let half_width = |text: &str| text.len() as f64 * font_size / 3.0;
+	let (_, board_height) = layout.get_board_size();
+
+	for col in 0..layout.dimensions.0 {
+		let coordinates: BoardCoordinates = (col, 0).into();
+		let file_name = coordinates.get_file_name();
+		let (x, _) = layout.square_centre(&coordinates);
+		let file_position = (x - half_width(&file_name), layout.origin.1 + board_height + layout.margin / 2.0 + font_size / 3.0);
+		backend.draw_text(&file_name, Font::Coordinates, font_size as u32, theme.coordinate_color, file_position);
+	}
+
+	for row in 0..layout.dimensions.1 {
+		let coordinates: BoardCoordinates = (0, row).into();
+		let rank_name = coordinates.get_rank_name();
+		let (_, y) = layout.square_centre(&coordinates);
+		let rank_position = (layout.origin.0 - layout.margin / 2.0 - half_width(&rank_name), y + font_size / 3.0);
+		backend.draw_text(&rank_name, Font::Coordinates, font_size as u32, theme.coordinate_color, rank_position);
+	}
+}
+
+// Fills a square with a (usually translucent) color on top of the board
+pub fn highlight_square(backend: &mut DrawBackend, layout: &BoardLayout, coordinates: &BoardCoordinates, color: [f32; 4]) {
+	let (x, y) = layout.square_position(coordinates);
+	backend.draw_rect([x, y, layout.square_size, layout.square_size], color);
+}
+
+// A dot in the middle of a square a piece can move to
+pub fn mark_square(backend: &mut DrawBackend, layout: &BoardLayout, coordinates: &BoardCoordinates, color: [f32; 4]) {
+	let (x, y) = layout.square_centre(coordinates);
+	let radius = layout.square_size / 6.0;
+	backend.draw_ellipse([x - radius, y - radius, radius * 2.0, radius * 2.0], color);
+}
+
+// An arrow between the centres of two squares
+pub fn draw_square_arrow(backend: &mut DrawBackend, layout: &BoardLayout, from: &BoardCoordinates, to: &BoardCoordinates, color: [f32; 4]) {
+	backend.draw_arrow(layout.square_centre(from), layout.square_centre(to), layout.square_size / 6.0, color);
+}
+
+// The outline of an arrow as the shaft quad and the head triangle, for backends that fill polygons
+pub fn arrow_polygons(from: (f64, f64), to: (f64, f64), width: f64) -> (Vec<[f64; 2]>, Vec<[f64; 2]>) {
+	let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+	let length = (dx * dx + dy * dy).sqrt().max(1.0);
+	let (ux, uy) = (dx / length, dy / length);
+	// Perpendicular to the arrow's direction
+	let (px, py) = (-uy, ux);
+
+	let head_length = (width * 2.5).min(length);
+	let base = (to.0 - ux * head_length, to.1 - uy * head_length);
+	let half = width / 2.0;
+
+	let shaft = vec![
+		[from.0 + px * half, from.1 + py * half],
+		[base.0 + px * half, base.1 + py * half],
+		[base.0 - px * half, base.1 - py * half],
+		[from.0 - px * half, from.1 - py * half]
+	];
+	let head = vec![
+		[base.0 + px * width * 1.5, base.1 + py * width * 1.5],
+		[to.0, to.1],
+		[base.0 - px * width * 1.5, base.1 - py * width * 1.5]
+	];
+
+	(shaft, head)
+}
@@ -0,0 +1,39 @@
+// Bounds checks: coordinates and squares that are not on a board are refused rather than panicking
+
+extern crate rust_chess;
+
+use rust_chess::*;
+
+#[test]
+fn coordinates_past_the_edge_are_refused() {
+	let mut board: ChessBoard<ChessPiece> = ChessBoard::new(8, 8);
+	for &position in &[(8, 0), (0, 8), (8, 8), (255, 255)] {
+		let coordinates: BoardCoordinates = position.into();
+		assert_eq!(board.get_contents_at_coordinates(&coordinates), Err(ChessError::OutOfBounds(coordinates)));
+		assert!(board.set_contents_at_coordinates(&coordinates, None).is_err());
+		assert!(board.get_square(&coordinates).is_err());
+	}
+	assert!(board.get_move_destination(&BoardCoordinates { col: 7, row: 7 }, 1, 0).is_none());
+	assert!(board.get_move_destination(&BoardCoordinates { col: 0, row: 0 }, -1, 0).is_none());
+}
+
+#[test]
+fn squares_of_another_sized_board_are_refused() {
+	let large: ChessBoard<ChessPiece> = ChessBoard::new(10, 10);
+	let mut board: ChessBoard<ChessPiece> = ChessBoard::new(8, 8);
+
+	let corner = large.get_square(&BoardCoordinates { col: 9, row: 9 }).unwrap();
+	assert!(board.get_cell(&corner).is_err());
+	assert!(board.get_square_contents(&corner).is_err());
+	assert!(board.set_square_contents(&corner, None).is_err());
+	assert!(board.offset_square(&corner, -2, -2).is_none());
+
+	// Even when it would fit, a square has to come from a board of the same size
+	let first = large.get_square(&BoardCoordinates { col: 0, row: 0 }).unwrap();
+	assert!(board.get_cell(&first).is_err());
+
+	let same_size: ChessBoard<ChessPiece> = ChessBoard::new(8, 8);
+	let square = same_size.get_square(&BoardCoordinates { col: 7, row: 7 }).unwrap();
+	assert!(board.get_cell(&square).is_ok());
+	assert_eq!(board.get_all_squares().len(), 64);
+}
@@ -0,0 +1,22 @@
+// Betza notation read into piece movements
+
+extern crate rust_chess;
+
+use rust_chess::*;
+
+fn steps(betza: &str) -> Vec<(i8, i8)> {
+	let mut steps: Vec<(i8, i8)> = PieceDefinition::new("Test", 'T', betza).unwrap().get_movements().iter().map(|movement| movement.step).collect();
+	steps.sort();
+	steps
+}
+
+#[test]
+fn compound_directions_are_one_direction() {
+	assert_eq!(steps("flF"), vec![(-1, 1)]);
+	assert_eq!(steps("fF"), vec![(-1, 1), (1, 1)]);
+	assert_eq!(steps("flN"), vec![(-2, 1), (-1, 2)]);
+	assert_eq!(steps("ffN"), vec![(-1, 2), (1, 2)]);
+	assert_eq!(steps("fsN"), vec![(-2, 1), (2, 1)]);
+	assert_eq!(steps("frlF"), vec![(-1, -1), (-1, 1), (1, 1)]);
+	assert!(PieceDefinition::new("Test", 'T', "fsF").is_err());
+}
@@ -0,0 +1,106 @@
+// Move counts from well known positions, so a change to move generation that gets any rule wrong
+// shows up as a wrong number of nodes
+
+extern crate rust_chess;
+
+use rust_chess::*;
+use std::path::Path;
+
+// Checks the number of positions after each depth from 1 up to the length of `counts`
+fn assert_perft(game: &ChessGame, counts: &[u64]) {
+	for (i, &count) in counts.iter().enumerate() {
+		let depth = i as u32 + 1;
+		assert_eq!(game.perft(depth), count, "perft {} of {}", depth, game.to_fen());
+	}
+}
+
+// A variant shipped in resources/variants
+fn load_variant(file_name: &str) -> Variant {
+	Variant::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/variants").join(file_name)).unwrap()
+}
+
+fn assert_fen_perft(fen: &str, counts: &[u64]) {
+	let game = ChessGame::from_fen(fen).unwrap();
+	assert_eq!(game.to_fen(), fen);
+	assert_perft(&game, counts);
+}
+
+#[test]
+fn start_position() {
+	assert_fen_perft(STARTING_FEN, &[20, 400, 8902, 197281]);
+}
+
+#[test]
+fn kiwipete() {
+	assert_fen_perft("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862]);
+}
+
+#[test]
+fn en_passant_and_pins() {
+	assert_fen_perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn promotions_and_castling_rights() {
+	assert_fen_perft("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467]);
+	assert_fen_perft("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379]);
+}
+
+// Black's only legal move is taking en passant, so the position is not stalemate
+#[test]
+fn en_passant_as_the_only_move() {
+	let fen = "7k/8/6Q1/8/3pP3/3P4/8/7K b - e3 0 1";
+	assert_eq!(ChessGame::from_fen(fen).unwrap().get_result(), None);
+	assert_fen_perft(fen, &[1]);
+}
+
+// Chess960 positions with castling rights given by file
+#[test]
+fn chess960_positions() {
+	let cases: [(&str, u64); 5] = [
+		("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", 12189),
+		("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", 18002),
+		("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", 10471),
+		("qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9", 13440),
+		("1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9", 31058)
+	];
+	for &(fen, count) in &cases {
+		let game = ChessGame::from_fen(fen).unwrap();
+		assert!(game.is_chess960(), "{} was not read as Chess960", fen);
+		assert_eq!(game.perft(3), count, "perft 3 of {}", fen);
+	}
+}
+
+#[test]
+fn chess960_start_positions_have_twenty_moves() {
+	for &index in &[0, 518, 959] {
+		let game = ChessGame::new_chess960(index).unwrap();
+		assert_eq!(game.perft(1), 20, "perft 1 of start position {}", index);
+	}
+}
+
+// The references listed in atomic.variant, played by the rules it defines
+#[test]
+fn atomic_positions() {
+	let rules = load_variant("atomic.variant").rules;
+	let cases: [(&str, &[u64]); 6] = [
+		(STARTING_FEN, &[20, 400, 8902]),
+		("rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1", &[40, 1238, 45237]),
+		("rn1qkb1r/p5pp/2p5/3p4/N3P3/5P2/PPP4P/R1BQK3 w Qkq - 0 1", &[28, 833, 23353]),
+		("8/8/8/8/8/8/2k5/rR4KR w KQ - 0 1", &[18, 180, 4364, 61401]),
+		("r3k1rR/5K2/8/8/8/8/8/8 b kq - 0 1", &[25, 282, 6753]),
+		("Rr2k1rR/3K4/3p4/8/8/8/7P/8 w kq - 0 1", &[21, 465, 10631])
+	];
+	for &(fen, counts) in &cases {
+		let mut game = ChessGame::from_fen(fen).unwrap();
+		game.set_rules(rules.clone());
+		assert_perft(&game, counts);
+	}
+}
+
+// The references listed in antichess.variant, where captures are forced and the king is an ordinary piece
+#[test]
+fn antichess_start_position() {
+	let game = ChessGame::from_variant(&load_variant("antichess.variant")).unwrap();
+	assert_perft(&game, &[20, 400, 8067, 153299]);
+}
@@ -0,0 +1,142 @@
+// Round trips through the JSON and binary forms, and saved data that has to be refused
+#![cfg(feature = "serialization")]
+
+extern crate rust_chess;
+
+use rust_chess::*;
+use rust_chess::serialization::{SavedBoard, SavedGame, SCHEMA_VERSION, from_bytes, from_json, to_bytes, to_json};
+
+fn play(game: &mut ChessGame, moves: &str) {
+	for text in moves.split_whitespace() {
+		let chess_move = game.parse_move(text).unwrap();
+		game.move_piece(&chess_move).unwrap();
+	}
+}
+
+// A game with a history, clocks that have both run and a result
+fn finished_game() -> ChessGame {
+	let mut game = ChessGame::new(ChessPieceColor::Black);
+	game.initialize_pieces();
+	game.set_time_control(300.0, 2.0);
+	play(&mut game, "f3 e5 g4");
+	game.update(7.5);
+	play(&mut game, "Qh4#");
+	game
+}
+
+fn assert_same_game(game: &ChessGame, read: &ChessGame) {
+	assert_eq!(read.to_fen(), game.to_fen());
+	assert_eq!(read.get_bottom_player_color(), game.get_bottom_player_color());
+	let notation = |game: &ChessGame| game.get_history().iter().map(|record| record.notation.clone()).collect::<Vec<String>>();
+	assert_eq!(notation(read), notation(game));
+	for &color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+		assert_eq!(read.get_clock(color), game.get_clock(color));
+	}
+	assert_eq!(read.get_result(), game.get_result());
+}
+
+fn assert_same_board(board: &ChessBoard<ChessPiece>, read: &ChessBoard<ChessPiece>) {
+	assert_eq!(read.get_dimensions(), board.get_dimensions());
+	for coordinates in board.get_all_coordinates() {
+		assert_eq!(read.get_contents_at_coordinates(&coordinates), board.get_contents_at_coordinates(&coordinates));
+	}
+}
+
+fn sample_board() -> ChessBoard<ChessPiece> {
+	let mut board = ChessBoard::new(10, 8);
+	board.set_contents_at_coordinates(&BoardCoordinates { col: 9, row: 7 }, Some(ChessPiece::new(ChessPieceType::Queen, ChessPieceColor::Black))).unwrap();
+	board.set_contents_at_coordinates(&BoardCoordinates { col: 0, row: 0 }, Some(ChessPiece::new(ChessPieceType::King, ChessPieceColor::White))).unwrap();
+	board
+}
+
+fn sample_move() -> ChessMove {
+	ChessMove::new(&BoardCoordinates::new("e7").unwrap(), &BoardCoordinates::new("e8").unwrap())
+		.with_promotion(ChessPieceType::Knight)
+}
+
+#[test]
+fn game_round_trips_through_json() {
+	let game = finished_game();
+	assert!(game.get_result().is_some());
+	assert!(game.get_clock(ChessPieceColor::Black).get_remaining() < 300.0);
+
+	let read: ChessGame = from_json(&to_json(&game).unwrap()).unwrap();
+	assert_same_game(&game, &read);
+}
+
+#[test]
+fn game_round_trips_through_bytes() {
+	let game = finished_game();
+	let read: ChessGame = from_bytes(&to_bytes(&game).unwrap()).unwrap();
+	assert_same_game(&game, &read);
+}
+
+#[test]
+fn board_round_trips() {
+	let board = sample_board();
+	assert_same_board(&board, &from_json(&to_json(&board).unwrap()).unwrap());
+	assert_same_board(&board, &from_bytes(&to_bytes(&board).unwrap()).unwrap());
+}
+
+#[test]
+fn piece_coordinates_and_move_round_trip() {
+	let piece = ChessPiece::new(ChessPieceType::Bishop, ChessPieceColor::Black);
+	assert_eq!(from_json::<ChessPiece>(&to_json(&piece).unwrap()).unwrap(), piece);
+	assert_eq!(from_bytes::<ChessPiece>(&to_bytes(&piece).unwrap()).unwrap(), piece);
+
+	let coordinates = BoardCoordinates::new("g6").unwrap();
+	assert_eq!(from_json::<BoardCoordinates>(&to_json(&coordinates).unwrap()).unwrap(), coordinates);
+	assert_eq!(from_bytes::<BoardCoordinates>(&to_bytes(&coordinates).unwrap()).unwrap(), coordinates);
+
+	let chess_move = sample_move();
+	assert_eq!(from_json::<ChessMove>(&to_json(&chess_move).unwrap()).unwrap(), chess_move);
+	assert_eq!(from_bytes::<ChessMove>(&to_bytes(&chess_move).unwrap()).unwrap(), chess_move);
+}
+
+#[test]
+fn piece_off_the_board_is_rejected() {
+	let mut saved: SavedBoard<ChessPiece> = sample_board().into();
+	saved.pieces.push((BoardCoordinates { col: 10, row: 0 }, ChessPiece::new(ChessPieceType::Rook, ChessPieceColor::White)));
+	assert!(from_json::<ChessBoard<ChessPiece>>(&to_json(&saved).unwrap()).is_err());
+	assert!(from_bytes::<ChessBoard<ChessPiece>>(&to_bytes(&saved).unwrap()).is_err());
+}
+
+#[test]
+fn empty_board_dimensions_are_rejected() {
+	for dimensions in &[(0, 8), (8, 0), (0, 0)] {
+		let saved: SavedBoard<ChessPiece> = SavedBoard { width: dimensions.0, height: dimensions.1, pieces: Vec::new() };
+		assert!(from_json::<ChessBoard<ChessPiece>>(&to_json(&saved).unwrap()).is_err());
+		assert!(from_bytes::<ChessBoard<ChessPiece>>(&to_bytes(&saved).unwrap()).is_err());
+	}
+}
+
+#[test]
+fn other_schema_versions_are_rejected() {
+	for &version in &[0, SCHEMA_VERSION + 1] {
+		let mut saved: SavedGame = finished_game().into();
+		saved.version = version;
+		assert!(from_json::<ChessGame>(&to_json(&saved).unwrap()).is_err());
+		assert!(from_bytes::<ChessGame>(&to_bytes(&saved).unwrap()).is_err());
+	}
+}
+
+#[test]
+fn squares_off_the_board_are_rejected() {
+	let mut saved: SavedGame = finished_game().into();
+	saved.en_passant = Some(BoardCoordinates { col: 3, row: 8 });
+	assert!(from_json::<ChessGame>(&to_json(&saved).unwrap()).is_err());
+
+	let mut saved: SavedGame = finished_game().into();
+	saved.promoted.push(BoardCoordinates { col: 200, row: 0 });
+	assert!(from_bytes::<ChessGame>(&to_bytes(&saved).unwrap()).is_err());
+}
+
+#[test]
+fn missing_fields_and_truncated_bytes_are_rejected() {
+	let json = to_json(&sample_board()).unwrap().replace("\"height\": 8,", "");
+	assert!(!json.contains("height"));
+	assert!(from_json::<ChessBoard<ChessPiece>>(&json).is_err());
+
+	let bytes = to_bytes(&finished_game()).unwrap();
+	assert!(from_bytes::<ChessGame>(&bytes[..bytes.len() - 1]).is_err());
+}
@@ -0,0 +1,18 @@
+// Reading theme manifests
+
+extern crate rust_chess;
+
+use rust_chess::theme::parse_color;
+
+#[test]
+fn colors_are_read_with_and_without_alpha() {
+	assert_eq!(parse_color("#ff0000"), Ok([1.0, 0.0, 0.0, 1.0]));
+	assert_eq!(parse_color("#00000000"), Ok([0.0, 0.0, 0.0, 0.0]));
+}
+
+#[test]
+fn malformed_colors_are_refused() {
+	for value in &["ff0000", "#ff00", "#ff00000", "#gg0000", "#+f+f+f", "#ééé", "#aééa"] {
+		assert!(parse_color(value).is_err(), "{}", value);
+	}
+}
@@ -0,0 +1,39 @@
+// Positions that could come about in a game of their variant, and ones that could not
+
+extern crate rust_chess;
+
+use rust_chess::*;
+use rust_chess::validate::PositionProblem;
+use std::path::Path;
+
+fn load_variant(file_name: &str) -> Variant {
+	Variant::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/variants").join(file_name)).unwrap()
+}
+
+#[test]
+fn variant_start_positions_are_valid() {
+	for file_name in &["capablanca.variant", "grand.variant", "gardner.variant", "silverman.variant", "crazyhouse.variant", "antichess.variant"] {
+		let game = ChessGame::from_variant(&load_variant(file_name)).unwrap();
+		assert_eq!(game.get_position_problems(), Vec::new(), "{}", file_name);
+	}
+}
+
+#[test]
+fn too_many_pawns_for_the_board() {
+	let game = ChessGame::from_fen("4k3/8/8/8/8/P7/PPPPPPPP/4K3 w - - 0 1").unwrap();
+	assert!(game.get_position_problems().contains(&PositionProblem::TooManyPawns(ChessPieceColor::White, 9, 8)));
+}
+
+// Grand chess pawns double step from the third rank, so they skip the fourth
+#[test]
+fn en_passant_follows_the_double_step_rank() {
+	let variant = load_variant("grand.variant");
+	let mut game = ChessGame::from_variant(&variant).unwrap();
+	let chess_move = game.parse_move("e5").unwrap();
+	game.move_piece(&chess_move).unwrap();
+	assert_eq!(game.get_en_passant(), Some(BoardCoordinates::new("e4").unwrap()));
+	assert_eq!(game.get_position_problems(), Vec::new());
+
+	game.set_en_passant(Some(BoardCoordinates::new("e3").unwrap()));
+	assert_eq!(game.get_position_problems(), vec![PositionProblem::EnPassant(BoardCoordinates::new("e3").unwrap())]);
+}